@@ -0,0 +1,342 @@
+use std::collections::BTreeMap;
+use chrono::{Duration, NaiveDateTime};
+use crate::item::due::{Due, DATE_LABEL_NAMES};
+use crate::item::item::Item;
+use crate::item::item_tree::ItemTree;
+
+/// Label name for a time estimate, such as `#estimate:1h30m`.
+pub static ESTIMATE_LABEL_NAME: &str = "estimate";
+
+/// Label name for time spent, such as `#spent:45m`.
+pub static SPENT_LABEL_NAME: &str = "spent";
+
+/// Label name for a clock entry, such as `#clock:2026-10-16T09:00:+1h30m`.
+pub static CLOCK_LABEL_NAME: &str = "clock";
+
+/// Label names that have durations as values.
+pub static DURATION_LABEL_NAMES: &[&str] = &["estimate", "spent"];
+
+/// Parse a duration, such as `1h30m`, `45m`, `2h`, or `90s`,
+/// which is one or more numbers each followed by a unit of
+/// `h` for hours, `m` for minutes, or `s` for seconds.
+///
+/// Example:
+///
+/// ```
+/// assert_eq!(parse_duration("1h30m"), Some(Duration::minutes(90)));
+/// assert_eq!(parse_duration("1.5h"), None);
+/// ```
+///
+/// Returns `None` if the duration is too long to be a `Duration`.
+///
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    if s.is_empty() {
+        return None;
+    }
+    let mut total = Duration::zero();
+    let mut n = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            n.push(c);
+            continue;
+        }
+        let n = std::mem::take(&mut n).parse::<i64>().ok()?;
+        let duration = match c.to_ascii_lowercase() {
+            'h' => Duration::try_hours(n)?,
+            'm' => Duration::try_minutes(n)?,
+            's' => Duration::try_seconds(n)?,
+            _ => return None,
+        };
+        total = total.checked_add(&duration)?;
+    }
+    if n.is_empty() { Some(total) } else { None }
+}
+
+/// Format a duration, such as `1h30m`, as the inverse of `parse_duration`,
+/// with a leading `-` if it is negative.
+pub fn format_duration(duration: Duration) -> String {
+    let sign = if duration < Duration::zero() { "-" } else { "" };
+    let seconds = duration.num_seconds().abs();
+    let (h, m, s) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    let mut str = String::from(sign);
+    if h > 0 {
+        str.push_str(&format!("{}h", h));
+    }
+    if m > 0 || (h == 0 && s == 0) {
+        str.push_str(&format!("{}m", m));
+    }
+    if s > 0 {
+        str.push_str(&format!("{}s", s));
+    }
+    str
+}
+
+/// A clock entry, which is a time that work started on an item,
+/// and how long it lasted, unless it is still running.
+///
+/// A clock entry is written as a label, such as
+/// `#clock:2026-10-16T09:00:+1h30m`, or `#clock:2026-10-16T09:00`
+/// while it is running.
+///
+/// Example:
+///
+/// ```
+/// let entry = ClockEntry::parse("2026-10-16T09:00:+1h30m").unwrap();
+/// assert_eq!(entry.duration, Some(Duration::minutes(90)));
+/// assert_eq!(entry.to_string(), "2026-10-16T09:00:+1h30m");
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockEntry {
+    pub start: NaiveDateTime,
+    pub duration: Option<Duration>,
+}
+
+impl ClockEntry {
+
+    /// Parse a clock entry from a label value.
+    pub fn parse(s: &str) -> Option<ClockEntry> {
+        let s = s.trim();
+        let (start, duration) = match s.rsplit_once(":+") {
+            Some((start, duration)) => (start, Some(parse_duration(duration)?)),
+            None => (s, None),
+        };
+        match Due::parse(start)? {
+            Due::DateTime(start) => Some(ClockEntry { start, duration }),
+            Due::Date(_) => None,
+        }
+    }
+
+    /// Is the clock entry still running?
+    pub fn is_running(&self) -> bool {
+        self.duration.is_none()
+    }
+
+}
+
+impl std::fmt::Display for ClockEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", Due::DateTime(self.start))?;
+        if let Some(duration) = self.duration {
+            write!(f, ":+{}", format_duration(duration))?;
+        }
+        Ok(())
+    }
+}
+
+/// Estimated and spent time, of one item or of many.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Effort {
+    pub estimate: Duration,
+    pub spent: Duration,
+}
+
+impl Default for Effort {
+    fn default() -> Self {
+        Effort { estimate: Duration::zero(), spent: Duration::zero() }
+    }
+}
+
+impl Effort {
+
+    /// Get the effort of one item, without its children.
+    pub fn of(item: &Item) -> Self {
+        Effort {
+            estimate: item.estimate().unwrap_or_else(Duration::zero),
+            spent: item.spent(),
+        }
+    }
+
+    /// Get how much the spent time is over the estimate,
+    /// which is negative if it is under the estimate.
+    pub fn over(&self) -> Duration {
+        self.spent - self.estimate
+    }
+
+}
+
+/// Add durations, or get the longest duration if the sum is too long.
+pub fn saturating_add(a: Duration, b: Duration) -> Duration {
+    a.checked_add(&b).unwrap_or(if b < Duration::zero() { Duration::MIN } else { Duration::MAX })
+}
+
+impl std::ops::Add for Effort {
+    type Output = Effort;
+    fn add(self, other: Effort) -> Effort {
+        Effort {
+            estimate: saturating_add(self.estimate, other.estimate),
+            spent: saturating_add(self.spent, other.spent),
+        }
+    }
+}
+
+impl std::ops::AddAssign for Effort {
+    fn add_assign(&mut self, other: Effort) {
+        *self = *self + other;
+    }
+}
+
+/// Roll up the effort of each node of a tree, which is the effort of
+/// its item plus the rolled up effort of its children.
+///
+/// Returns the effort of each node, by node id.
+///
+/// Example:
+///
+/// ```
+/// let items = load_items_via_str("[ ] a #estimate:1h\n    [ ] b #estimate:30m\n").unwrap();
+/// let tree = ItemTree::from_items(items);
+/// assert_eq!(rollup_efforts(&tree)[0].estimate, Duration::minutes(90));
+/// ```
+///
+pub fn rollup_efforts(tree: &ItemTree) -> Vec<Effort> {
+    let mut efforts: Vec<Effort> = tree.nodes.iter().map(|x| Effort::of(&x.item)).collect();
+    // Children are after their parents, so go in reverse to add each child before its parent.
+    for id in (0..tree.nodes.len()).rev() {
+        if let Some(parent) = tree[id].parent {
+            let effort = efforts[id];
+            efforts[parent] += effort;
+        }
+    }
+    efforts
+}
+
+/// Report the effort of items for each label, such as `#client:acme`,
+/// which is the sum of the effort of each item with the label.
+///
+/// Labels for effort and due dates, such as `#estimate`, `#spent`,
+/// `#clock`, and `#due`, are not in the report, and each item counts
+/// only its own effort, so that nested items are not counted twice.
+///
+/// Example:
+///
+/// ```
+/// let report = report_efforts_by_label(&items);
+/// for (label, effort) in report {
+///     println!("{} {}", label, format_duration(effort.over()));
+/// }
+/// ```
+///
+pub fn report_efforts_by_label(items: &[Item]) -> BTreeMap<String, Effort> {
+    let mut report: BTreeMap<String, Effort> = BTreeMap::new();
+    for item in items {
+        let effort = Effort::of(item);
+        let mut names: Vec<String> = Vec::new();
+        for label in item.labels.iter().flatten() {
+            if [ESTIMATE_LABEL_NAME, SPENT_LABEL_NAME, CLOCK_LABEL_NAME].contains(&label.name())
+                || DATE_LABEL_NAMES.contains(&label.name()) {
+                continue;
+            }
+            let name = label.to_string();
+            if !names.contains(&name) {
+                *report.entry(name.clone()).or_default() += effort;
+                names.push(name);
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use crate::item::item_parser;
+    use crate::load::load_items_via_str;
+    use crate::save::save_items_via_string;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("1h30m"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("45m"), Some(Duration::minutes(45)));
+        assert_eq!(parse_duration("2H"), Some(Duration::hours(2)));
+        assert_eq!(parse_duration("90s"), Some(Duration::seconds(90)));
+        assert_eq!(parse_duration("30m1h"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("99999999999999h"), None);
+        assert_eq!(parse_duration("5000000000000000s5000000000000000s"), None);
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("45"), None);
+        assert_eq!(parse_duration("h"), None);
+        assert_eq!(parse_duration("1.5h"), None);
+        assert_eq!(parse_duration("1d"), None);
+    }
+
+    #[test]
+    fn test_parse_duration_too_long_with_diagnostics() {
+        let (items, diagnostics) = item_parser::parse("[ ] a #estimate:99999999999999h #spent:99999999999999h\n");
+        assert_eq!(items[0].estimate(), None);
+        assert_eq!(items[0].spent(), Duration::zero());
+        assert_eq!(diagnostics.len(), 2);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::minutes(90)), "1h30m");
+        assert_eq!(format_duration(Duration::hours(2)), "2h");
+        assert_eq!(format_duration(Duration::seconds(90)), "1m30s");
+        assert_eq!(format_duration(Duration::zero()), "0m");
+        assert_eq!(format_duration(Duration::minutes(-45)), "-45m");
+    }
+
+    #[test]
+    fn test_clock_entry() {
+        let entry = ClockEntry::parse("2026-10-16T09:00:+1h30m").unwrap();
+        assert_eq!(entry.start.to_string(), "2026-10-16 09:00:00");
+        assert_eq!(entry.duration, Some(Duration::minutes(90)));
+        assert_eq!(entry.to_string(), "2026-10-16T09:00:+1h30m");
+        let entry = ClockEntry::parse("2026-10-16T09:00:30").unwrap();
+        assert!(entry.is_running());
+        assert_eq!(entry.to_string(), "2026-10-16T09:00:30");
+        assert_eq!(ClockEntry::parse("2026-10-16"), None);
+        assert_eq!(ClockEntry::parse("2026-10-16T09:00:+soon"), None);
+    }
+
+    #[test]
+    fn test_clock_in_and_out_with_save() {
+        let mut items = load_items_via_str("- [ ] foo\n- [ ] goo\n").unwrap();
+        let now = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        items[0].clock_in(now);
+        assert_eq!(save_items_via_string(&items), "- [ ] foo\n      #clock:2026-10-16T09:00\n- [ ] goo\n");
+        items[0].clock_out(now + Duration::minutes(90));
+        assert_eq!(save_items_via_string(&items), "- [ ] foo\n      #clock:2026-10-16T09:00:+1h30m\n- [ ] goo\n");
+    }
+
+    #[test]
+    fn test_rollup_efforts() {
+        let str = indoc!{"
+            [ ] a #estimate:1h
+                [ ] a1 #estimate:30m #spent:45m
+                    [ ] a1i #spent:15m
+                [ ] a2 #estimate:2h
+                    #clock:2026-10-16T09:00:+1h30m
+            [ ] b #spent:10m
+        "};
+        let tree = ItemTree::from_items(load_items_via_str(str).unwrap());
+        let efforts = rollup_efforts(&tree);
+        assert_eq!(efforts[0], Effort { estimate: Duration::minutes(210), spent: Duration::minutes(150) });
+        assert_eq!(efforts[1], Effort { estimate: Duration::minutes(30), spent: Duration::minutes(60) });
+        assert_eq!(efforts[1].over(), Duration::minutes(30));
+        assert_eq!(efforts[4], Effort { estimate: Duration::zero(), spent: Duration::minutes(10) });
+    }
+
+    #[test]
+    fn test_report_efforts_by_label() {
+        let str = indoc!{"
+            [ ] a #client:acme #estimate:1h #spent:30m #due:2026-10-20
+            [ ] b #client:acme #billable #billable #estimate:1h #spent:2h
+            [ ] c #client:zeta #spent:15m
+        "};
+        let report = report_efforts_by_label(&load_items_via_str(str).unwrap());
+        let actual: Vec<(String, String)> = report.iter()
+            .map(|(label, effort)| (label.clone(), format_duration(effort.over())))
+            .collect();
+        assert_eq!(actual, vec![
+            ("#billable".into(), "1h".into()),
+            ("#client:acme".into(), "30m".into()),
+            ("#client:zeta".into(), "15m".into()),
+        ]);
+    }
+
+}
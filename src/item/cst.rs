@@ -0,0 +1,365 @@
+use crate::item::item::Item;
+use crate::item::status::Status;
+
+/// Concrete syntax tree of an item, which keeps every token of its
+/// original text, so that one token can be edited without
+/// reformatting the rest of the file.
+///
+/// The tokens of the first line are in the order they appear,
+/// such as `"  "`, `"-"`, `" "`, `"["`, `"x"`, `"]"`, `" "`, `"foo"`,
+/// `"\t"`, `"\n"` for the line `"  - [x] foo\t\n"`.
+///
+/// Example:
+///
+/// ```
+/// let items = load_items_via_str("\n- [ ] foo\n      goo\n").unwrap();
+/// let cst = items[0].cst.as_ref().unwrap();
+/// assert_eq!(cst.lead, "\n");
+/// assert_eq!(cst.list_marker, "-");
+/// assert_eq!(cst.memo, "foo");
+/// assert_eq!(cst.continuation_lines[0].indent, "      ");
+/// assert_eq!(cst.to_string(), "\n- [ ] foo\n      goo\n");
+/// ```
+///
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ItemCst {
+    /// Blank lines and headings before the item, and any other text
+    /// before it that is not in an item, such as front matter and prose.
+    ///
+    /// In a document, this text is in the parts before the item instead.
+    ///
+    pub lead: String,
+    /// Indentation before the list marker, such as `"    "` or `"\t"`.
+    pub indent: String,
+    /// List marker, such as `"-"` or `"•"`, or empty if there is none.
+    pub list_marker: String,
+    /// Whitespace between the list marker and the checkbox.
+    pub list_marker_spacer: String,
+    /// Checkbox open, such as `"["` or `"［"`.
+    pub checkbox_open: String,
+    /// Checkbox mark, such as `"x"` or `"✓"`.
+    pub checkbox_mark: String,
+    /// Checkbox shut, such as `"]"` or `"］"`.
+    pub checkbox_shut: String,
+    /// Whitespace between the checkbox and the memo.
+    pub checkbox_suffix: String,
+    /// Memo text of the first line.
+    pub memo: String,
+    /// Trailing whitespace of the first line.
+    pub memo_suffix: String,
+    /// Line ending of the first line, such as `"\n"` or `"\r\n"`,
+    /// or empty at the end of a file without a final line ending.
+    pub line_ending: String,
+    /// Lines after the first line that continue the memo.
+    pub continuation_lines: Vec<LineCst>,
+    /// Blank lines and headings after the item, at the end of the file,
+    /// and any other text after it, as for the lead.
+    pub tail: String,
+    /// The item as it was parsed, to know whether it has changed.
+    pub loaded: Box<Item>,
+}
+
+/// Concrete syntax tree of a continuation line of an item.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LineCst {
+    /// Indentation before the memo text.
+    pub indent: String,
+    /// Memo text of the line.
+    pub memo: String,
+    /// Trailing whitespace of the line.
+    pub memo_suffix: String,
+    /// Line ending of the line.
+    pub line_ending: String,
+}
+
+/// Indent for each nest level.
+static NEST_INDENT_DEFAULT: &str = "    ";
+
+/// Checkbox tokens for a note that gets a mark.
+static CHECKBOX_OPEN_DEFAULT: &str = "[";
+static CHECKBOX_SHUT_DEFAULT: &str = "]";
+static CHECKBOX_SUFFIX_DEFAULT: &str = " ";
+
+impl LineCst {
+
+    /// Create a line from text, splitting off its indentation and trailing whitespace.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let line = LineCst::new("  foo \t", "\n");
+    /// assert_eq!(line.indent, "  ");
+    /// assert_eq!(line.memo, "foo");
+    /// assert_eq!(line.memo_suffix, " \t");
+    /// ```
+    ///
+    pub fn new(s: &str, line_ending: &str) -> Self {
+        let trim_start = s.trim_start();
+        let memo = trim_start.trim_end();
+        LineCst {
+            indent: s[..s.len() - trim_start.len()].into(),
+            memo: memo.into(),
+            memo_suffix: trim_start[memo.len()..].into(),
+            line_ending: line_ending.into(),
+        }
+    }
+
+}
+
+impl std::fmt::Display for LineCst {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}{}{}{}", self.indent, self.memo, self.memo_suffix, self.line_ending)
+    }
+}
+
+impl ItemCst {
+
+    /// Does the first line have a checkbox?
+    ///
+    /// Text that is not in an item, such as a leading paragraph,
+    /// has only continuation lines.
+    ///
+    pub fn has_checkbox(&self) -> bool {
+        !self.checkbox_open.is_empty()
+    }
+
+    /// Does the item have a first line, with a checkbox or a list marker?
+    ///
+    /// A note has a list marker without a checkbox, such as `- foo`.
+    ///
+    pub fn has_first_line(&self) -> bool {
+        self.has_checkbox() || !self.list_marker.is_empty()
+    }
+
+    /// Get the text of the first line before the memo, such as `- [ ] `.
+    pub fn memo_prefix(&self) -> String {
+        format!("{}{}{}{}{}{}{}",
+            self.indent,
+            self.list_marker,
+            self.list_marker_spacer,
+            self.checkbox_open,
+            self.checkbox_mark,
+            self.checkbox_shut,
+            self.checkbox_suffix,
+        )
+    }
+
+    /// Get the indentation for a new continuation line.
+    ///
+    /// This is the indentation of the first continuation line that is
+    /// not blank if there is one, otherwise it is whitespace as wide as
+    /// the memo prefix, so that continuation lines line up with the memo.
+    ///
+    pub fn continuation_indent(&self) -> String {
+        match self.continuation_lines.iter().find(|x| !x.memo.is_empty()) {
+            Some(line) => line.indent.clone(),
+            None => self.memo_prefix().chars().map(|c| if c == '\t' { '\t' } else { ' ' }).collect(),
+        }
+    }
+
+    /// Set the checkbox mark token, and add a checkbox to a note.
+    pub fn set_mark(&mut self, mark: Status) {
+        if !self.has_checkbox() && !self.list_marker.is_empty() {
+            self.checkbox_open = CHECKBOX_OPEN_DEFAULT.into();
+            self.checkbox_shut = CHECKBOX_SHUT_DEFAULT.into();
+            self.checkbox_suffix = CHECKBOX_SUFFIX_DEFAULT.into();
+        }
+        self.checkbox_mark = mark.to_string();
+    }
+
+    /// Remove the checkbox tokens, which makes an item with a list marker a note.
+    pub fn remove_checkbox(&mut self) {
+        if !self.list_marker.is_empty() {
+            self.checkbox_open.clear();
+            self.checkbox_mark.clear();
+            self.checkbox_shut.clear();
+            self.checkbox_suffix.clear();
+        }
+    }
+
+    /// Set the indentation of the first line for a nest level,
+    /// and shift the continuation lines by the same amount.
+    pub fn set_nest(&mut self, nest: u8) {
+        let indent = NEST_INDENT_DEFAULT.repeat(nest as usize);
+        for line in self.continuation_lines.iter_mut().filter(|x| !x.memo.is_empty()) {
+            line.indent = match line.indent.strip_prefix(&self.indent) {
+                Some(x) => format!("{}{}", indent, x),
+                None => format!("{}{}", indent, line.indent),
+            };
+        }
+        self.indent = indent;
+    }
+
+    /// Set the memo tokens from memo text.
+    ///
+    /// Lines that are the same as before keep their original tokens.
+    /// Other lines use the continuation indent, except blank lines,
+    /// and the line endings of the original lines.
+    ///
+    pub fn set_memo(&mut self, memo: &str) {
+        let mut lines = memo.split('\n');
+        if self.has_first_line() {
+            self.memo = lines.next().unwrap_or_default().into();
+        }
+        let line_ending_default = self.line_ending_default();
+        let line_ending_last = self.continuation_lines.last().map_or(&self.line_ending, |x| &x.line_ending).clone();
+        let continuation_indent = self.continuation_indent();
+        let old_lines = std::mem::take(&mut self.continuation_lines);
+        for (i, line) in lines.enumerate() {
+            self.continuation_lines.push(match old_lines.get(i) {
+                Some(old_line) if old_line.memo == line => old_line.clone(),
+                _ => LineCst {
+                    indent: if line.is_empty() { String::new() } else { continuation_indent.clone() },
+                    memo: line.into(),
+                    memo_suffix: String::new(),
+                    line_ending: line_ending_default.clone(),
+                },
+            });
+        }
+        // Only the last line can be without a line ending.
+        if self.has_first_line() {
+            self.line_ending = line_ending_default.clone();
+        }
+        for line in self.continuation_lines.iter_mut() {
+            line.line_ending = line_ending_default.clone();
+        }
+        match self.continuation_lines.last_mut() {
+            Some(line) => line.line_ending = line_ending_last,
+            None => self.line_ending = line_ending_last,
+        }
+    }
+
+    /// Update the tokens for the changes to an item since it was parsed.
+    pub fn update(&mut self, item: &Item) {
+        if item.mark != self.loaded.mark {
+            match item.mark {
+                Some(mark) => self.set_mark(mark),
+                None => self.remove_checkbox(),
+            }
+        }
+        if item.nest != self.loaded.nest {
+            self.set_nest(item.nest.unwrap_or(0));
+        }
+        if item.memo != self.loaded.memo || item.labels != self.loaded.labels {
+            self.set_memo(&item.memo_with_labels());
+        }
+    }
+
+    /// Get the text of the lines of the item, without the blank lines
+    /// and headings before or after it.
+    pub fn body(&self) -> String {
+        let mut s = format!("{}{}{}{}",
+            self.memo_prefix(),
+            self.memo,
+            self.memo_suffix,
+            self.line_ending,
+        );
+        for line in self.continuation_lines.iter() {
+            s.push_str(&line.to_string());
+        }
+        s
+    }
+
+    /// Get the line ending that most lines use, or `\n` if there are none.
+    fn line_ending_default(&self) -> String {
+        std::iter::once(&self.line_ending)
+            .chain(self.continuation_lines.iter().map(|x| &x.line_ending))
+            .find(|x| !x.is_empty())
+            .map_or("\n".into(), |x| x.clone())
+    }
+
+}
+
+/// Write all the tokens, which is the original text when nothing has changed.
+impl std::fmt::Display for ItemCst {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}{}{}", self.lead, self.body(), self.tail)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cst() -> ItemCst {
+        ItemCst {
+            lead: "\n".into(),
+            indent: "  ".into(),
+            list_marker: "-".into(),
+            list_marker_spacer: " ".into(),
+            checkbox_open: "［".into(), // U+FF3B FULLWIDTH LEFT SQUARE BRACKET
+            checkbox_mark: " ".into(),
+            checkbox_shut: "］".into(), // U+FF3D FULLWIDTH RIGHT SQUARE BRACKET
+            checkbox_suffix: " ".into(),
+            memo: "foo".into(),
+            memo_suffix: "\t".into(),
+            line_ending: "\r\n".into(),
+            continuation_lines: vec![
+                LineCst::new("        goo ", "\r\n"),
+            ],
+            tail: "\n".into(),
+            loaded: Box::default(),
+        }
+    }
+
+    #[test]
+    fn test_fmt() {
+        assert_eq!(cst().to_string(), "\n  - ［ ］ foo\t\r\n        goo \r\n\n");
+    }
+
+    #[test]
+    fn test_body() {
+        assert_eq!(cst().body(), "  - ［ ］ foo\t\r\n        goo \r\n");
+    }
+
+    #[test]
+    fn test_line_cst_new() {
+        let line = LineCst::new(" \t foo goo  ", "");
+        assert_eq!(line.indent, " \t ");
+        assert_eq!(line.memo, "foo goo");
+        assert_eq!(line.memo_suffix, "  ");
+        assert_eq!(line.line_ending, "");
+    }
+
+    #[test]
+    fn test_continuation_indent() {
+        let mut cst = cst();
+        assert_eq!(cst.continuation_indent(), "        ");
+        cst.continuation_lines.clear();
+        cst.indent = "\t".into();
+        assert_eq!(cst.continuation_indent(), "\t      ");
+    }
+
+    #[test]
+    fn test_set_mark() {
+        let mut cst = cst();
+        cst.set_mark(Status::Done);
+        assert_eq!(cst.to_string(), "\n  - ［x］ foo\t\r\n        goo \r\n\n");
+    }
+
+    #[test]
+    fn test_set_nest() {
+        let mut cst = cst();
+        cst.set_nest(1);
+        assert_eq!(cst.to_string(), "\n    - ［ ］ foo\t\r\n          goo \r\n\n");
+    }
+
+    #[test]
+    fn test_set_memo() {
+        let mut cst = cst();
+        cst.set_memo("foo\ngoo\nhoo");
+        assert_eq!(cst.to_string(), "\n  - ［ ］ foo\t\r\n        goo \r\n        hoo\r\n\n");
+        cst.set_memo("joo");
+        assert_eq!(cst.to_string(), "\n  - ［ ］ joo\t\r\n\n");
+    }
+
+    #[test]
+    fn test_set_memo_without_final_line_ending() {
+        let mut cst = cst();
+        cst.continuation_lines[0].line_ending = "".into();
+        cst.tail = "".into();
+        cst.set_memo("foo\ngoo\nhoo");
+        assert_eq!(cst.to_string(), "\n  - ［ ］ foo\t\r\n        goo \r\n        hoo");
+    }
+
+}
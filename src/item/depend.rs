@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use crate::item::diagnostic::{Diagnostic, Severity};
+use crate::item::item::Item;
+use crate::item::label::find_labels;
+use crate::item::status::Status;
+
+/// Label name for an item id, such as `#id:venue`.
+pub static ID_LABEL_NAME: &str = "id";
+
+/// Label name for a prerequisite, such as `#after:venue`,
+/// which means this item can start after the item `venue` is done.
+pub static AFTER_LABEL_NAME: &str = "after";
+
+/// Label name for a dependent, such as `#blocks:invites`,
+/// which means the item `invites` can start after this item is done.
+pub static BLOCKS_LABEL_NAME: &str = "blocks";
+
+/// A reference from an item to an id that no item has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dangling {
+    /// Index of the item with the reference.
+    pub index: usize,
+    /// Label name of the reference, such as `after` or `blocks`.
+    pub name: String,
+    /// The id that no item has.
+    pub id: String,
+}
+
+/// A graph of the dependencies of a list of items, from their
+/// `#id`, `#after`, and `#blocks` labels, by item index.
+///
+/// Example:
+///
+/// ```
+/// let items = load_items_via_str("[x] Book venue #id:venue\n[ ] Send invites #after:venue\n").unwrap();
+/// let graph = DependencyGraph::new(&items);
+/// assert_eq!(graph.prerequisites[1], vec![0]);
+/// assert!(!graph.is_blocked(&items, 1));
+/// ```
+///
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DependencyGraph {
+    /// Item index for each id; the first item with an id has it.
+    pub ids: HashMap<String, usize>,
+    /// Prerequisites for each item, as item indexes, in order.
+    pub prerequisites: Vec<Vec<usize>>,
+    /// References to ids that no item has.
+    pub dangling: Vec<Dangling>,
+}
+
+impl DependencyGraph {
+
+    /// Build the graph of a list of items.
+    pub fn new(items: &[Item]) -> Self {
+        let mut graph = DependencyGraph {
+            prerequisites: vec![Vec::new(); items.len()],
+            ..Default::default()
+        };
+        for (index, item) in items.iter().enumerate() {
+            if let Some(id) = item.id() {
+                graph.ids.entry(id).or_insert(index);
+            }
+        }
+        for (index, item) in items.iter().enumerate() {
+            for label in item.labels.iter().flatten() {
+                let (name, id) = match label.value() {
+                    Some(id) if label.name() == AFTER_LABEL_NAME || label.name() == BLOCKS_LABEL_NAME => (label.name(), id),
+                    _ => continue,
+                };
+                let other = match graph.ids.get(&id) {
+                    Some(other) => *other,
+                    None => {
+                        graph.dangling.push(Dangling { index, name: name.into(), id });
+                        continue;
+                    }
+                };
+                let (dependent, prerequisite) = if name == AFTER_LABEL_NAME { (index, other) } else { (other, index) };
+                if !graph.prerequisites[dependent].contains(&prerequisite) {
+                    graph.prerequisites[dependent].push(prerequisite);
+                }
+            }
+        }
+        graph
+    }
+
+    /// Is an item blocked, because any of its prerequisites is not done?
+    ///
+    /// An item in a cycle is always blocked.
+    ///
+    pub fn is_blocked(&self, items: &[Item], index: usize) -> bool {
+        self.prerequisites[index].iter().any(|x| items[*x].mark != Some(Status::Done))
+    }
+
+    /// Get whether each item is blocked, by item index.
+    pub fn blocked(&self, items: &[Item]) -> Vec<bool> {
+        (0..items.len()).map(|x| self.is_blocked(items, x)).collect()
+    }
+
+    /// Find cycles of dependencies, where each cycle is a list of item
+    /// indexes such that each item is a prerequisite of the one before it,
+    /// and the last item is a prerequisite of the first.
+    ///
+    /// Each cycle is found once, starting at its lowest item index.
+    ///
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        let mut cycles: Vec<Vec<usize>> = Vec::new();
+        // 0 is not visited, 1 is on the path, 2 is done.
+        let mut state = vec![0u8; self.prerequisites.len()];
+        let mut path: Vec<usize> = Vec::new();
+        for start in 0..self.prerequisites.len() {
+            self.visit(start, &mut state, &mut path, &mut cycles);
+        }
+        for cycle in cycles.iter_mut() {
+            let min = (0..cycle.len()).min_by_key(|x| cycle[*x]).unwrap_or(0);
+            cycle.rotate_left(min);
+        }
+        cycles.sort();
+        cycles.dedup();
+        cycles
+    }
+
+    fn visit(&self, index: usize, state: &mut [u8], path: &mut Vec<usize>, cycles: &mut Vec<Vec<usize>>) {
+        match state[index] {
+            1 => {
+                let at = path.iter().position(|x| *x == index).unwrap_or(0);
+                cycles.push(path[at..].to_vec());
+                return;
+            },
+            2 => return,
+            _ => {},
+        }
+        state[index] = 1;
+        path.push(index);
+        for prerequisite in self.prerequisites[index].iter() {
+            self.visit(*prerequisite, state, path, cycles);
+        }
+        path.pop();
+        state[index] = 2;
+    }
+
+    /// Get diagnostics for dangling references and cycles,
+    /// with spans in the source text that the items were parsed from.
+    ///
+    /// Items without spans, such as new items, have no diagnostics.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let source = "[ ] foo #after:goo\n";
+    /// let (items, _) = parse(source);
+    /// let diagnostics = DependencyGraph::new(&items).diagnostics(&items, source);
+    /// assert_eq!(diagnostics[0].to_string(), "1:9: warning: no item has the id `goo`");
+    /// ```
+    ///
+    pub fn diagnostics(&self, items: &[Item], source: &str) -> Vec<Diagnostic> {
+        let mut vec = Vec::new();
+        for dangling in self.dangling.iter() {
+            if let Some(span) = label_span(&items[dangling.index], source, &dangling.name, &dangling.id) {
+                let message = format!("no item has the id `{}`", dangling.id);
+                vec.push(Diagnostic::new(Severity::Warning, message, source, span));
+            }
+        }
+        for cycle in self.cycles() {
+            let names: Vec<String> = cycle.iter().chain(cycle.first())
+                .map(|x| items[*x].id().unwrap_or_else(|| format!("item {}", x + 1)))
+                .collect();
+            let first = &items[cycle[0]];
+            let span = first.id()
+                .and_then(|id| label_span(first, source, ID_LABEL_NAME, &id))
+                .or_else(|| first.span.as_ref().map(|x| x.bytes.clone()));
+            if let Some(span) = span {
+                let message = format!("dependency cycle: {}", names.join(" -> "));
+                vec.push(Diagnostic::new(Severity::Warning, message, source, span));
+            }
+        }
+        vec.sort_by_key(|x| x.span.start);
+        vec
+    }
+
+}
+
+/// Find the byte range of the first label of an item with a name and value,
+/// in the source text that the item was parsed from.
+fn label_span(item: &Item, source: &str, name: &str, value: &str) -> Option<std::ops::Range<usize>> {
+    let bytes = item.span.as_ref()?.bytes.clone();
+    find_labels(&source[bytes.clone()]).into_iter()
+        .find(|(_, x)| x.name() == name && x.value().as_deref() == Some(value))
+        .map(|(range, _)| bytes.start + range.start..bytes.start + range.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::item_parser::parse;
+    use indoc::indoc;
+
+    #[test]
+    fn test_new() {
+        let source = indoc!{"
+            [ ] Book venue #id:venue
+            [ ] Order food #id:food #after:venue #blocks:party
+            [ ] Party #id:party #after:venue #after:cake
+        "};
+        let (items, _) = parse(source);
+        let graph = DependencyGraph::new(&items);
+        assert_eq!(graph.ids.get("food"), Some(&1));
+        assert_eq!(graph.prerequisites, vec![vec![], vec![0], vec![1, 0]]);
+        assert_eq!(graph.dangling, vec![Dangling { index: 2, name: "after".into(), id: "cake".into() }]);
+    }
+
+    #[test]
+    fn test_blocked() {
+        let source = indoc!{"
+            [x] Book venue #id:venue
+            [ ] Order food #id:food #after:venue
+            [ ] Party #after:food #after:venue
+            [ ] Clean up
+        "};
+        let (mut items, _) = parse(source);
+        let graph = DependencyGraph::new(&items);
+        assert_eq!(graph.blocked(&items), vec![false, false, true, false]);
+        items[1].mark = Some(Status::Done);
+        assert_eq!(graph.blocked(&items), vec![false, false, false, false]);
+    }
+
+    #[test]
+    fn test_cycles() {
+        let source = indoc!{"
+            [ ] a #id:a #after:c
+            [ ] b #id:b #after:a
+            [ ] c #id:c #after:b
+            [ ] d #id:d #after:d
+            [ ] e #id:e #after:a
+        "};
+        let (items, _) = parse(source);
+        let graph = DependencyGraph::new(&items);
+        assert_eq!(graph.cycles(), vec![vec![0, 2, 1], vec![3]]);
+        assert_eq!(graph.blocked(&items), vec![true, true, true, true, true]);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let source = indoc!{"
+            [ ] a #id:a #after:b
+            [ ] b #id:b #blocks:a #after:a
+                #after:nope
+        "};
+        let (items, _) = parse(source);
+        let actual: Vec<String> = DependencyGraph::new(&items).diagnostics(&items, source).iter().map(|x| x.to_string()).collect();
+        assert_eq!(actual, vec![
+            "1:7: warning: dependency cycle: a -> b -> a",
+            "3:5: warning: no item has the id `nope`",
+        ]);
+    }
+
+}
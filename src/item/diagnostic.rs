@@ -0,0 +1,183 @@
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// Severity of a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The text can not be parsed.
+    Error,
+    /// The text can be parsed, but is likely a typo.
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A message about a problem in source text, with its position.
+///
+/// Example:
+///
+/// ```
+/// let source = "[ ] foo\n[xx] goo\n";
+/// let diagnostic = Diagnostic::new(Severity::Warning, "checkbox has more than one mark", source, 8..12)
+///     .with_fix("[x]");
+/// assert_eq!(diagnostic.line, 2);
+/// assert_eq!(diagnostic.column, 1);
+/// assert_eq!(diagnostic.to_string(), "2:1: warning: checkbox has more than one mark");
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// Column number in chars, starting at 1.
+    pub column: usize,
+    /// Byte range in the source text.
+    pub span: Range<usize>,
+    /// Suggested replacement text for the span.
+    pub fix: Option<String>,
+    /// Path of the file of the source text, if it is not the file that
+    /// was loaded, such as a file that is included by it.
+    pub file: Option<PathBuf>,
+}
+
+impl Diagnostic {
+
+    /// Create a diagnostic for a byte range of source text,
+    /// which calculates the line and column of the range start.
+    pub fn new(severity: Severity, message: impl Into<String>, source: &str, span: Range<usize>) -> Self {
+        let before = &source[..span.start];
+        let line_start = before.rfind('\n').map_or(0, |x| x + 1);
+        Diagnostic {
+            severity,
+            message: message.into(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            span,
+            fix: None,
+            file: None,
+        }
+    }
+
+    /// Set the suggested replacement text for the span.
+    pub fn with_fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+
+    /// Set the path of the file of the source text.
+    pub fn with_file(mut self, file: impl Into<PathBuf>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Render the diagnostic in the style of rustc, with the source line
+    /// and a caret under the span.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let source = "[ ] foo\n[xx] goo\n";
+    /// let diagnostic = Diagnostic::new(Severity::Warning, "checkbox has more than one mark", source, 8..12)
+    ///     .with_fix("[x]");
+    /// assert_eq!(diagnostic.render("todo.txt", source), indoc!{"
+    ///     warning: checkbox has more than one mark
+    ///      --> todo.txt:2:1
+    ///       |
+    ///     2 | [xx] goo
+    ///       | ^^^^ help: replace with `[x]`
+    /// "});
+    /// ```
+    ///
+    pub fn render(&self, name: &str, source: &str) -> String {
+        let line_start = source[..self.span.start].rfind('\n').map_or(0, |x| x + 1);
+        let line_end = source[line_start..].find('\n').map_or(source.len(), |x| line_start + x);
+        let line_str = source[line_start..line_end].trim_end_matches('\r');
+        let span_end = self.span.end.clamp(self.span.start, line_start + line_str.len());
+        let gutter = " ".repeat(self.line.to_string().len());
+        // Keep tabs in the padding, so the caret lines up with the source line.
+        let padding: String = line_str[..self.span.start - line_start].chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let carets = "^".repeat(source[self.span.start..span_end].chars().count().max(1));
+        let mut s = String::new();
+        s.push_str(&format!("{}: {}\n", self.severity, self.message));
+        s.push_str(&format!("{}--> {}:{}:{}\n", gutter, name, self.line, self.column));
+        s.push_str(&format!("{} |\n", gutter));
+        s.push_str(&format!("{} | {}\n", self.line, line_str));
+        s.push_str(&format!("{} | {}{}", gutter, padding, carets));
+        if let Some(fix) = &self.fix {
+            s.push_str(&format!(" help: replace with `{}`", fix));
+        }
+        s.push('\n');
+        s
+    }
+
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+        }
+        write!(f, "{}:{}: {}: {}", self.line, self.column, self.severity, self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_new() {
+        let source = "[ ] foo\r\n\t• ［αβ］ goo\n";
+        let diagnostic = Diagnostic::new(Severity::Warning, "foo", source, 14..24);
+        assert_eq!(diagnostic.line, 2);
+        assert_eq!(diagnostic.column, 4);
+    }
+
+    #[test]
+    fn test_fmt() {
+        let diagnostic = Diagnostic::new(Severity::Error, "foo", "\n\ngoo", 3..4);
+        assert_eq!(diagnostic.to_string(), "3:2: error: foo");
+        let diagnostic = diagnostic.with_file("areas/work.txt");
+        assert_eq!(diagnostic.to_string(), "areas/work.txt:3:2: error: foo");
+    }
+
+    #[test]
+    fn test_render() {
+        let source = "[ ] foo\n\t- [ goo\n";
+        let diagnostic = Diagnostic::new(Severity::Warning, "checkbox is not shut", source, 11..13)
+            .with_fix("[ ] ");
+        assert_eq!(diagnostic.render("todo.txt", source), indoc!{"
+            warning: checkbox is not shut
+             --> todo.txt:2:4
+              |
+            2 | \t- [ goo
+              | \t  ^^ help: replace with `[ ] `
+        "});
+    }
+
+    #[test]
+    fn test_render_with_empty_span_and_no_fix() {
+        let source = "foo\r\n";
+        let diagnostic = Diagnostic::new(Severity::Error, "goo", source, 3..3);
+        assert_eq!(diagnostic.render("-", source), indoc!{"
+            error: goo
+             --> -:1:4
+              |
+            1 | foo
+              |    ^
+        "});
+    }
+
+}
@@ -0,0 +1,338 @@
+use crate::item::item::Item;
+use crate::item::item_parser;
+use crate::item::meta::ListMeta;
+use crate::item::node::Node;
+
+/// A document of items, which is its metadata, and its items in order
+/// with the text between them, such as headings, include directives,
+/// comments, and prose.
+///
+/// The text between items is part of the document, not of an item,
+/// so it stays in place as items are removed, inserted, or reordered,
+/// and a document without items keeps its text.
+///
+/// Example:
+///
+/// ```
+/// let (mut document, _) = parse_document("Intro\n\n# Work\n[ ] foo\n[ ] goo\n");
+/// document.remove_item(0);
+/// assert_eq!(save_document_via_string(&document), "Intro\n\n# Work\n[ ] goo\n");
+/// ```
+///
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Document {
+    pub meta: ListMeta,
+    pub parts: Vec<Part>,
+}
+
+/// A part of a document, which is an item, or text between items.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Part {
+    /// An item or a note.
+    Item(Box<Item>),
+    /// Text that is not in an item, which is headings, include directives,
+    /// comments, and paragraphs of prose, each with the blank lines before it,
+    /// as it was loaded.
+    Text(String),
+}
+
+impl Document {
+
+    /// Create a document from items that keep the text around them, as
+    /// the items of `load_items_via_str` do, which is the front matter and
+    /// the text before an item in its lead, and the text after the last
+    /// item in its tail, as in `parse_document`.
+    ///
+    /// The text before an item stays before it, so it moves with the item,
+    /// and the first front matter is the metadata, so it stays at the start.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let items = load_items_via_str("# Work\n[ ] foo\n").unwrap();
+    /// let document = Document::from_items(items);
+    /// assert_eq!(document.parts[0], Part::Text("# Work\n".into()));
+    /// ```
+    ///
+    pub fn from_items(items: impl IntoIterator<Item = Item>) -> Document {
+        let mut document = Document::default();
+        for mut item in items {
+            let (front_matter, lead, tail) = take_text(&mut item);
+            match front_matter {
+                Some(x) if document.meta.loaded.is_none() => document.meta = ListMeta::parse(&x),
+                Some(x) => document.parts.push(Part::Text(x)),
+                None => {},
+            }
+            document.parts.extend(lead.map(Part::Text));
+            document.parts.push(Part::Item(Box::new(item)));
+            document.parts.extend(tail.map(Part::Text));
+        }
+        document
+    }
+
+    /// Iterate over the items, in order.
+    pub fn items(&self) -> impl Iterator<Item = &Item> {
+        self.parts.iter().filter_map(|x| match x {
+            Part::Item(item) => Some(item.as_ref()),
+            Part::Text(_) => None,
+        })
+    }
+
+    /// Iterate over the items as mutable, in order.
+    pub fn items_mut(&mut self) -> impl Iterator<Item = &mut Item> {
+        self.parts.iter_mut().filter_map(|x| match x {
+            Part::Item(item) => Some(item.as_mut()),
+            Part::Text(_) => None,
+        })
+    }
+
+    /// Convert the document to its items, in order, which keep the text
+    /// around them, as in `from_items`, so the items are as they are loaded
+    /// via `load_items_via_str`.
+    ///
+    /// The front matter and the text before an item are in the lead of the
+    /// next item that was loaded, and the text after it is in the tail of the
+    /// last item that was loaded. A document without such items has no item
+    /// to keep its text.
+    ///
+    pub fn into_items(self) -> Vec<Item> {
+        let mut items: Vec<Item> = Vec::new();
+        let mut text = self.meta.to_front_matter();
+        for part in self.parts {
+            match part {
+                Part::Item(mut item) => {
+                    if let Some(cst) = item.cst.as_mut() {
+                        cst.lead.insert_str(0, &std::mem::take(&mut text));
+                    }
+                    items.push(*item);
+                },
+                Part::Text(x) => text.push_str(&x),
+            }
+        }
+        if let Some(cst) = items.iter_mut().rev().find_map(|x| x.cst.as_mut()) {
+            cst.tail.push_str(&text);
+        }
+        items
+    }
+
+    /// Set the items, such as after sorting them, and keep the text
+    /// between items in place.
+    ///
+    /// The items take the places of the items of the document in order.
+    /// Any more items are after the last place, or at the end if the
+    /// document has no items, and any places that are left are removed.
+    ///
+    /// The text that the items keep around them, as in `from_items`,
+    /// is not used, because the document has its own text.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let (mut document, _) = parse_document("# Work\n[ ] foo\n[ ] goo\n");
+    /// let mut items = document.items().cloned().collect::<Vec<_>>();
+    /// items.reverse();
+    /// document.set_items(items);
+    /// assert_eq!(save_document_via_string(&document), "# Work\n[ ] goo\n[ ] foo\n");
+    /// ```
+    ///
+    pub fn set_items(&mut self, items: Vec<Item>) {
+        let mut items = items.into_iter();
+        let mut parts = Vec::with_capacity(self.parts.len());
+        let mut end = None;
+        for part in std::mem::take(&mut self.parts) {
+            match part {
+                Part::Item(_) => if let Some(mut item) = items.next() {
+                    take_text(&mut item);
+                    parts.push(Part::Item(Box::new(item)));
+                    end = Some(parts.len());
+                },
+                Part::Text(_) => parts.push(part),
+            }
+        }
+        let end = end.unwrap_or(parts.len());
+        parts.splice(end..end, items.map(|mut x| { take_text(&mut x); Part::Item(Box::new(x)) }));
+        self.parts = parts;
+    }
+
+    /// Insert an item at an index of the items, which is in the place of
+    /// the item at the index, after any text before that item, or else
+    /// after the last item.
+    ///
+    /// The text that the item keeps around it is not used, as in `set_items`.
+    ///
+    /// Panics if the index is greater than the number of items, as `Vec::insert` does.
+    ///
+    pub fn insert_item(&mut self, index: usize, mut item: Item) {
+        take_text(&mut item);
+        let places = self.item_places();
+        assert!(index <= places.len(), "item index {} is out of range for {} items", index, places.len());
+        let at = match places.get(index) {
+            Some(at) => *at,
+            None => places.last().map_or(self.parts.len(), |x| x + 1),
+        };
+        self.parts.insert(at, Part::Item(Box::new(item)));
+    }
+
+    /// Remove the item at an index of the items, and keep the text around it.
+    ///
+    /// Panics if the index is out of range, as `Vec::remove` does.
+    ///
+    pub fn remove_item(&mut self, index: usize) -> Item {
+        let places = self.item_places();
+        assert!(index < places.len(), "item index {} is out of range for {} items", index, places.len());
+        match self.parts.remove(places[index]) {
+            Part::Item(item) => *item,
+            Part::Text(_) => unreachable!("an item place is an item"),
+        }
+    }
+
+    /// Get the nodes of the document, in order, which are its items,
+    /// and the headings, include directives, comments, and prose of
+    /// the text between them.
+    pub fn nodes(&self) -> Vec<Node> {
+        let mut vec = Vec::new();
+        for part in self.parts.iter() {
+            match part {
+                Part::Item(item) => vec.push(Node::Item(item.clone())),
+                Part::Text(text) => vec.extend(item_parser::lead_nodes(text)),
+            }
+        }
+        vec
+    }
+
+    /// Get the indexes of the parts that are items, in order.
+    fn item_places(&self) -> Vec<usize> {
+        self.parts.iter().enumerate()
+            .filter(|(_, x)| matches!(x, Part::Item(_)))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+}
+
+/// Take the text around an item that a document keeps apart from it,
+/// which is any front matter at the start of its lead, the rest of its
+/// lead, and its tail, each unless it is only blank lines.
+fn take_text(item: &mut Item) -> (Option<String>, Option<String>, Option<String>) {
+    let cst = match item.cst.as_mut() {
+        Some(cst) => cst,
+        None => return (None, None, None),
+    };
+    let front_matter = match item_parser::front_matter(&cst.lead) {
+        Ok((_, front_matter_str)) => Some(cst.lead.drain(..front_matter_str.len()).collect()),
+        Err(_) => None,
+    };
+    let lead = (!cst.lead.trim().is_empty()).then(|| std::mem::take(&mut cst.lead));
+    let tail = (!cst.tail.trim().is_empty()).then(|| std::mem::take(&mut cst.tail));
+    (front_matter, lead, tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::item_parser::parse_document;
+    use crate::item::status::Status;
+    use crate::save::save_document_via_string;
+    use indoc::indoc;
+
+    fn new_item(memo: &str) -> Item {
+        Item {
+            nest: Some(0),
+            mark: Some(Status::Todo),
+            memo: Some(memo.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_document() {
+        let (document, _) = parse_document(indoc!{"
+            ---
+            title: Party
+            ---
+            Intro
+
+            # Work
+            [ ] foo
+
+            [ ] goo
+            // comment
+
+            Outro
+        "});
+        assert_eq!(document.meta.title(), Some("Party"));
+        let memos: Vec<_> = document.items().map(|x| x.memo.clone().unwrap()).collect();
+        assert_eq!(memos, vec!["foo", "goo"]);
+        assert!(matches!(&document.parts[0], Part::Text(x) if x == "Intro\n\n# Work\n"));
+        assert!(matches!(&document.parts[3], Part::Text(x) if x == "// comment\n\nOutro\n"));
+        assert_eq!(document.items().nth(1).unwrap().cst.as_ref().unwrap().lead, "\n");
+    }
+
+    #[test]
+    fn test_remove_item() {
+        let str = indoc!{"
+            Intro prose
+
+            # Work
+            [ ] a
+            [ ] b
+        "};
+        let (mut document, _) = parse_document(str);
+        assert_eq!(document.remove_item(0).memo, Some("a".into()));
+        assert_eq!(save_document_via_string(&document), "Intro prose\n\n# Work\n[ ] b\n");
+        document.remove_item(0);
+        assert_eq!(save_document_via_string(&document), "Intro prose\n\n# Work\n");
+    }
+
+    #[test]
+    fn test_insert_item() {
+        let (mut document, _) = parse_document("Intro\n\n[ ] foo\n\nOutro\n");
+        document.insert_item(1, new_item("goo"));
+        document.insert_item(0, new_item("hoo"));
+        assert_eq!(save_document_via_string(&document), "Intro\n\n[ ] hoo\n[ ] foo\n[ ] goo\n\nOutro\n");
+    }
+
+    #[test]
+    fn test_set_items_with_sort() {
+        let str = indoc!{"
+            Intro prose
+
+            # Work
+            [ ] a #priority:2
+            [ ] b #priority:1
+        "};
+        let (mut document, _) = parse_document(str);
+        let items = crate::item::priority::sort_items_by_priority(document.items().cloned().collect(), None);
+        document.set_items(items);
+        assert_eq!(save_document_via_string(&document), indoc!{"
+            Intro prose
+
+            # Work
+            [ ] b #priority:1
+            [ ] a #priority:2
+        "});
+    }
+
+    #[test]
+    fn test_set_items_with_fewer_and_more_items() {
+        let (mut document, _) = parse_document("[ ] foo\n// comment\n[ ] goo\n");
+        document.set_items(vec![new_item("hoo")]);
+        assert_eq!(save_document_via_string(&document), "[ ] hoo\n// comment\n");
+        document.set_items(vec![new_item("hoo"), new_item("joo")]);
+        assert_eq!(save_document_via_string(&document), "[ ] hoo\n[ ] joo\n// comment\n");
+        let (mut document, _) = parse_document("Only prose\n");
+        document.set_items(vec![new_item("foo")]);
+        assert_eq!(save_document_via_string(&document), "Only prose\n[ ] foo\n");
+    }
+
+    #[test]
+    fn test_nodes() {
+        let (document, _) = parse_document("# Work\n[ ] foo\n#include:work.txt\n");
+        let nodes = document.nodes();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[0], Node::Heading(crate::item::section::Heading::new(1, "Work")));
+        assert!(matches!(&nodes[1], Node::Item(x) if x.memo == Some("foo".into())));
+        assert_eq!(nodes[2], Node::Include("work.txt".into()));
+    }
+
+}
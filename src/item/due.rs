@@ -0,0 +1,264 @@
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Weekday};
+
+/// Label name for a due date, such as `#due:2026-10-20`.
+pub static DUE_LABEL_NAME: &str = "due";
+
+/// Label name for a defer date, such as `#defer:2026-10-20`,
+/// which is when to start an item.
+pub static DEFER_LABEL_NAME: &str = "defer";
+
+/// Label names that have dates as values.
+pub static DATE_LABEL_NAMES: &[&str] = &["due", "defer"];
+
+/// A due date, with or without a time of day.
+///
+/// A due date is written as a label, such as `#due:2026-10-20`
+/// or `#due:2026-10-20T17:00`.
+///
+/// Example:
+///
+/// ```
+/// let due = Due::parse("2026-10-20T17:00").unwrap();
+/// assert_eq!(due.date(), NaiveDate::from_ymd_opt(2026, 10, 20).unwrap());
+/// assert_eq!(due.to_string(), "2026-10-20T17:00");
+/// assert_eq!(Due::parse("2026-02-30"), None);
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Due {
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+}
+
+impl Due {
+
+    /// Parse a due date from text, such as `2026-10-20`, `2026-10-20T17:00`,
+    /// or `2026-10-20T17:00:30`.
+    ///
+    /// Returns `None` if the text is not a date, or is an impossible date.
+    ///
+    pub fn parse(s: &str) -> Option<Due> {
+        let s = s.trim();
+        if let Ok(x) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Some(Due::Date(x));
+        }
+        ["%Y-%m-%dT%H:%M", "%Y-%m-%dT%H:%M:%S"].iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+            .map(Due::DateTime)
+    }
+
+    /// Parse a due date from text that is either an absolute date,
+    /// or a date relative to now, such as:
+    ///
+    ///   * `today`, `tomorrow`, `yesterday`
+    ///   * a weekday, such as `fri` or `friday`, which is the next one on or after today
+    ///   * an offset, such as `+3d`, `+2w`, `+1m`, or `+1y`
+    ///   * `eow`, `eom`, `eoy` for the end of the week, month, or year
+    ///   * `next-week`, `next-month`, `next-year` for the start of the next one
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let now = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap().and_hms_opt(9, 0, 0).unwrap();
+    /// assert_eq!(Due::parse_relative("+3d", now), Due::parse("2026-10-19"));
+    /// ```
+    ///
+    pub fn parse_relative(s: &str, now: NaiveDateTime) -> Option<Due> {
+        if let Some(x) = Due::parse(s) {
+            return Some(x);
+        }
+        let today = now.date();
+        let s = s.trim().to_lowercase();
+        let date = match s.as_str() {
+            "today" => today,
+            "tomorrow" => today.succ_opt()?,
+            "yesterday" => today.pred_opt()?,
+            "eow" => next_weekday(today, Weekday::Sun),
+            "eom" => today.with_day(1)?.checked_add_months(Months::new(1))?.pred_opt()?,
+            "eoy" => NaiveDate::from_ymd_opt(today.year(), 12, 31)?,
+            "next-week" => next_weekday(today.succ_opt()?, Weekday::Mon),
+            "next-month" => today.with_day(1)?.checked_add_months(Months::new(1))?,
+            "next-year" => NaiveDate::from_ymd_opt(today.year() + 1, 1, 1)?,
+            _ => match s.parse::<Weekday>() {
+                Ok(weekday) => next_weekday(today, weekday),
+                Err(_) => add_offset(today, &s)?,
+            },
+        };
+        Some(Due::Date(date))
+    }
+
+    /// Is the text a relative date, such as `tomorrow`,
+    /// rather than an absolute date?
+    pub fn is_relative(s: &str) -> bool {
+        Due::parse(s).is_none() && Due::parse_relative(s, NaiveDateTime::default()).is_some()
+    }
+
+    /// Get the date.
+    pub fn date(&self) -> NaiveDate {
+        match self {
+            Due::Date(x) => *x,
+            Due::DateTime(x) => x.date(),
+        }
+    }
+
+    /// Get the last moment that is not late; a date without a time
+    /// is due by the end of the day.
+    pub fn deadline(&self) -> NaiveDateTime {
+        match self {
+            Due::Date(x) => x.and_time(NaiveTime::from_hms_opt(23, 59, 59).unwrap()),
+            Due::DateTime(x) => *x,
+        }
+    }
+
+    /// Is this due on or before a date?
+    pub fn is_by(&self, date: NaiveDate) -> bool {
+        self.date() <= date
+    }
+
+}
+
+/// Due dates are in order of their deadlines.
+impl Ord for Due {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.deadline().cmp(&other.deadline())
+            .then_with(|| matches!(self, Due::DateTime(_)).cmp(&matches!(other, Due::DateTime(_))))
+    }
+}
+
+impl PartialOrd for Due {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for Due {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Due::Date(x) => write!(f, "{}", x.format("%Y-%m-%d")),
+            Due::DateTime(x) if x.second() == 0 => write!(f, "{}", x.format("%Y-%m-%dT%H:%M")),
+            Due::DateTime(x) => write!(f, "{}", x.format("%Y-%m-%dT%H:%M:%S")),
+        }
+    }
+}
+
+/// Add an offset to a date, such as `+3d` for 3 days,
+/// or `+2w`, `+1m`, `+1y` for weeks, months, or years.
+fn add_offset(date: NaiveDate, s: &str) -> Option<NaiveDate> {
+    let s = s.strip_prefix('+')?;
+    let (n, unit) = s.split_at(s.char_indices().last()?.0);
+    let n: u32 = n.parse().ok()?;
+    match unit {
+        "d" => date.checked_add_signed(Duration::try_days(n as i64)?),
+        "w" => date.checked_add_signed(Duration::try_weeks(n as i64)?),
+        "m" => date.checked_add_months(Months::new(n)),
+        "y" => date.checked_add_months(Months::new(n.checked_mul(12)?)),
+        _ => None,
+    }
+}
+
+/// Get the next date that is a weekday, on or after a date,
+/// such as the Friday of this week.
+///
+/// Example:
+///
+/// ```
+/// let monday = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+/// let friday = next_weekday(monday, Weekday::Fri);
+/// assert_eq!(friday, NaiveDate::from_ymd_opt(2026, 10, 23).unwrap());
+/// ```
+///
+pub fn next_weekday(date: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days = (7 + weekday.num_days_from_monday() - date.weekday().num_days_from_monday()) % 7;
+    date + Duration::days(days as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Due::parse("2026-10-20"), Some(Due::Date(date(2026, 10, 20))));
+        assert_eq!(Due::parse("2026-10-20T17:00"), Some(Due::DateTime(date(2026, 10, 20).and_hms_opt(17, 0, 0).unwrap())));
+        assert_eq!(Due::parse("2026-10-20T17:00:30"), Some(Due::DateTime(date(2026, 10, 20).and_hms_opt(17, 0, 30).unwrap())));
+        assert_eq!(Due::parse("2026-02-30"), None);
+        assert_eq!(Due::parse("2026-10-20T25:00"), None);
+        assert_eq!(Due::parse("friday"), None);
+    }
+
+    #[test]
+    fn test_parse_relative() {
+        let now = date(2026, 10, 16).and_hms_opt(9, 0, 0).unwrap(); // Friday
+        let relative = |s| Due::parse_relative(s, now).map(|x| x.to_string());
+        assert_eq!(relative("2026-10-20T17:00"), Some("2026-10-20T17:00".into()));
+        assert_eq!(relative("today"), Some("2026-10-16".into()));
+        assert_eq!(relative("Tomorrow"), Some("2026-10-17".into()));
+        assert_eq!(relative("yesterday"), Some("2026-10-15".into()));
+        assert_eq!(relative("fri"), Some("2026-10-16".into()));
+        assert_eq!(relative("monday"), Some("2026-10-19".into()));
+        assert_eq!(relative("+3d"), Some("2026-10-19".into()));
+        assert_eq!(relative("+2w"), Some("2026-10-30".into()));
+        assert_eq!(relative("+4m"), Some("2027-02-16".into()));
+        assert_eq!(relative("+1y"), Some("2027-10-16".into()));
+        assert_eq!(relative("eow"), Some("2026-10-18".into()));
+        assert_eq!(relative("eom"), Some("2026-10-31".into()));
+        assert_eq!(relative("eoy"), Some("2026-12-31".into()));
+        assert_eq!(relative("next-week"), Some("2026-10-19".into()));
+        assert_eq!(relative("next-month"), Some("2026-11-01".into()));
+        assert_eq!(relative("next-year"), Some("2027-01-01".into()));
+        assert_eq!(relative("+3"), None);
+        assert_eq!(relative("+d"), None);
+        assert_eq!(relative("soon"), None);
+    }
+
+    #[test]
+    fn test_is_relative() {
+        assert!(Due::is_relative("tomorrow"));
+        assert!(Due::is_relative("+3d"));
+        assert!(!Due::is_relative("2026-10-20"));
+        assert!(!Due::is_relative("soon"));
+        assert!(!Due::is_relative("+3日"));
+        assert!(!Due::is_relative("+3é"));
+        assert!(!Due::is_relative("+4294967295w"));
+    }
+
+    #[test]
+    fn test_fmt() {
+        for s in ["2026-10-20", "2026-10-20T17:00", "2026-10-20T17:00:30"] {
+            assert_eq!(Due::parse(s).unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn test_ord() {
+        let mut dues = [
+            Due::parse("2026-10-21").unwrap(),
+            Due::parse("2026-10-20").unwrap(),
+            Due::parse("2026-10-20T17:00").unwrap(),
+            Due::parse("2026-10-20T23:59:59").unwrap(),
+        ];
+        dues.sort();
+        let actual: Vec<String> = dues.iter().map(|x| x.to_string()).collect();
+        assert_eq!(actual, vec!["2026-10-20T17:00", "2026-10-20", "2026-10-20T23:59:59", "2026-10-21"]);
+    }
+
+    #[test]
+    fn test_is_by() {
+        let due = Due::parse("2026-10-20T17:00").unwrap();
+        assert!(due.is_by(date(2026, 10, 20)));
+        assert!(due.is_by(date(2026, 10, 21)));
+        assert!(!due.is_by(date(2026, 10, 19)));
+    }
+
+    #[test]
+    fn test_next_weekday() {
+        assert_eq!(next_weekday(date(2026, 10, 19), Weekday::Fri), date(2026, 10, 23));
+        assert_eq!(next_weekday(date(2026, 10, 23), Weekday::Fri), date(2026, 10, 23));
+        assert_eq!(next_weekday(date(2026, 10, 24), Weekday::Fri), date(2026, 10, 30));
+    }
+
+}
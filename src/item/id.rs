@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use crate::item::item::Item;
+use crate::item::label::find_labels;
+
+/// Number of hex digits of a content hash id.
+static HASH_ID_LEN: usize = 6;
+
+/// Get a short hash of the content of an item, as hex digits,
+/// which is the same after the item is reformatted, or its mark,
+/// nesting, or labels change.
+///
+/// The hash is of the memo text without labels, with each run of
+/// whitespace as one space, using FNV-1a so that it is the same
+/// on every platform and every build.
+///
+/// Example:
+///
+/// ```
+/// // item memo is "Water   plants #due:2026-10-20"
+/// assert_eq!(content_hash(&item), content_hash_of_text("Water plants"));
+/// ```
+///
+pub fn content_hash(item: &Item) -> String {
+    let memo = item.memo.as_deref().unwrap_or_default();
+    let mut text = String::new();
+    let mut at = 0;
+    for (range, _) in find_labels(memo) {
+        text.push_str(&memo[at..range.start]);
+        text.push(' ');
+        at = range.end;
+    }
+    text.push_str(&memo[at..]);
+    content_hash_of_text(&text)
+}
+
+/// Get a short hash of text, with each run of whitespace as one space.
+pub fn content_hash_of_text(text: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for (i, word) in text.split_whitespace().enumerate() {
+        for byte in (if i == 0 { "" } else { " " }).bytes().chain(word.bytes()) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    format!("{:016x}", hash)[..HASH_ID_LEN].to_string()
+}
+
+/// Stable ids of a list of items, with lookup by id.
+///
+/// The id of an item is its `#id` label value, if any, else its content hash.
+/// When ids collide, the first item in the list has the id, and each later
+/// item has the id with a suffix of its count, such as `a1b2c3-2`.
+///
+/// Example:
+///
+/// ```
+/// let items = load_items_via_str("[ ] Book venue #id:venue\n[ ] Water plants\n[x] Water plants\n").unwrap();
+/// let ids = ItemIds::new(&items);
+/// assert_eq!(ids.id(0), "venue");
+/// assert_eq!(ids.get("venue"), Some(0));
+/// assert_eq!(ids.id(2), format!("{}-2", ids.id(1)));
+/// ```
+///
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ItemIds {
+    /// Id of each item, by item index.
+    pub ids: Vec<String>,
+    /// Item index of each id.
+    pub indexes: HashMap<String, usize>,
+}
+
+impl ItemIds {
+
+    /// Get the ids of a list of items.
+    pub fn new(items: &[Item]) -> Self {
+        let mut item_ids = ItemIds::default();
+        // Explicit ids first, so that a content hash never takes an explicit id.
+        let bases: Vec<(String, bool)> = items.iter()
+            .map(|x| match x.id() {
+                Some(id) => (id, true),
+                None => (content_hash(x), false),
+            })
+            .collect();
+        let mut ids: Vec<Option<String>> = vec![None; items.len()];
+        for explicit in [true, false] {
+            for (index, (base, _)) in bases.iter().enumerate().filter(|(_, (_, x))| *x == explicit) {
+                let mut id = base.clone();
+                let mut count = 1;
+                while item_ids.indexes.contains_key(&id) {
+                    count += 1;
+                    id = format!("{}-{}", base, count);
+                }
+                item_ids.indexes.insert(id.clone(), index);
+                ids[index] = Some(id);
+            }
+        }
+        item_ids.ids = ids.into_iter().flatten().collect();
+        item_ids
+    }
+
+    /// Get the id of an item by its index.
+    pub fn id(&self, index: usize) -> &str {
+        &self.ids[index]
+    }
+
+    /// Get the index of an item by its id.
+    pub fn get(&self, id: &str) -> Option<usize> {
+        self.indexes.get(id).copied()
+    }
+
+}
+
+/// Find an item by its stable id, as in `ItemIds`.
+pub fn find_item_by_id<'a>(items: &'a [Item], id: &str) -> Option<&'a Item> {
+    ItemIds::new(items).get(id).map(|x| &items[x])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load::load_items_via_str;
+    use indoc::indoc;
+
+    #[test]
+    fn test_content_hash() {
+        let items = load_items_via_str(indoc!{"
+            [ ] Water plants #due:2026-10-20
+            - [x] Water
+                  plants #every:week
+            [ ] Water flowers
+        "}).unwrap();
+        assert_eq!(content_hash(&items[0]).len(), 6);
+        assert_eq!(content_hash(&items[0]), content_hash(&items[1]));
+        assert_ne!(content_hash(&items[0]), content_hash(&items[2]));
+        assert_eq!(content_hash_of_text("Water plants"), content_hash(&items[0]));
+        assert_eq!(content_hash_of_text(""), "cbf29c");
+    }
+
+    #[test]
+    fn test_item_ids() {
+        let hash = content_hash_of_text("Water plants");
+        let items = load_items_via_str(&format!(indoc!{"
+            [ ] Water plants
+            [ ] Book venue #id:venue
+            [ ] Water plants
+            [ ] Other venue #id:venue
+            [ ] Explicit #id:{}
+        "}, hash)).unwrap();
+        let ids = ItemIds::new(&items);
+        assert_eq!(ids.ids, vec![
+            format!("{}-2", hash),
+            "venue".into(),
+            format!("{}-3", hash),
+            "venue-2".into(),
+            hash.clone(),
+        ]);
+        assert_eq!(ids.get("venue-2"), Some(3));
+        assert_eq!(ids.get(&hash), Some(4));
+        assert_eq!(ids.get("nope"), None);
+        assert_eq!(find_item_by_id(&items, "venue").and_then(|x| x.memo.clone()), Some("Book venue #id:venue".into()));
+    }
+
+}
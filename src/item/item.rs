@@ -1,36 +1,430 @@
+use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
+use crate::item::cst::ItemCst;
+use crate::item::depend::ID_LABEL_NAME;
+use chrono::{Duration, NaiveDate, NaiveDateTime, Timelike};
+use crate::item::clock::{parse_duration, saturating_add, ClockEntry, CLOCK_LABEL_NAME, ESTIMATE_LABEL_NAME, SPENT_LABEL_NAME};
+use crate::item::due::{Due, DATE_LABEL_NAMES, DEFER_LABEL_NAME, DUE_LABEL_NAME};
+use crate::item::label::{find_labels, labels_from_memo, Label, LabelDedup};
+use crate::item::priority::{Priority, PRIORITY_LABEL_NAME};
+use crate::item::render::{render_item, RenderOptions};
+use crate::item::span::Span;
+use crate::item::status::Status;
 
-#[derive(Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Item {
     pub nest: Option<u8>,
-    pub mark: Option<String>,
+    pub mark: Option<Status>,
     pub memo: Option<String>,
-    pub label1s: Option<Vec<String>>,
-    pub label2s: Option<Vec<(String, String)>>,
+    pub labels: Option<Vec<Label>>,
+    /// Texts of the headings that the item is under, from the outermost,
+    /// such as `["Work", "Website"]`, or `None` if it is under no heading.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<Vec<String>>,
+    /// Path of the file that the item was loaded from, such as a file
+    /// that is included by another file, or `None` if it is a new item.
+    #[serde(skip)]
+    pub file: Option<PathBuf>,
+    #[serde(skip)]
+    pub cst: Option<ItemCst>,
+    #[serde(skip)]
+    pub span: Option<Span>,
+}
+
+/// A default item is a to do item, with a checkbox, rather than a note.
+impl Default for Item {
+    fn default() -> Self {
+        Item {
+            nest: None,
+            mark: Some(Status::Todo),
+            memo: None,
+            labels: None,
+            section: None,
+            file: None,
+            cst: None,
+            span: None,
+        }
+    }
+}
+
+/// Items are equal when their content is equal,
+/// regardless of where they came from or how they were formatted,
+/// and regardless of the section and the file that they are in.
+impl PartialEq for Item {
+    fn eq(&self, other: &Self) -> bool {
+        self.nest == other.nest &&
+        self.mark == other.mark &&
+        self.memo == other.memo &&
+        self.labels == other.labels
+    }
+}
+
+impl Item {
+
+    /// Has this item changed since it was loaded?
+    ///
+    /// An item that was not loaded, such as a new item, has changed.
+    ///
+    pub fn is_changed(&self) -> bool {
+        match &self.cst {
+            Some(cst) => *cst.loaded != *self,
+            None => true,
+        }
+    }
+
+    /// Is this a note, which has no checkbox, such as a bullet `- foo`
+    /// under an item?
+    pub fn is_note(&self) -> bool {
+        self.mark.is_none()
+    }
+
+    /// Is this item in a section or in one of its subsections?
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// // item section is ["Work", "Website"]
+    /// assert!(item.is_in_section(&["Work"]));
+    /// assert!(!item.is_in_section(&["Home"]));
+    /// ```
+    ///
+    pub fn is_in_section(&self, section: &[&str]) -> bool {
+        let path = self.section.as_deref().unwrap_or_default();
+        path.len() >= section.len() && path.iter().zip(section).all(|(a, b)| a == b)
+    }
+
+    /// Get the labels that are the same as the prefix, or under the prefix.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// // item memo is "foo #project:website:design #project:mobile"
+    /// let prefix = Label::new(["project", "website"]);
+    /// let labels = item.labels_with_prefix(&prefix);
+    /// assert_eq!(labels, vec![&Label::new(["project", "website", "design"])]);
+    /// ```
+    ///
+    pub fn labels_with_prefix(&self, prefix: &Label) -> Vec<&Label> {
+        self.labels.iter().flatten().filter(|x| x.starts_with(prefix)).collect()
+    }
+
+    /// Does this item have any label that is the same as the prefix, or under the prefix?
+    pub fn has_label_with_prefix(&self, prefix: &Label) -> bool {
+        self.labels.iter().flatten().any(|x| x.starts_with(prefix))
+    }
+
+    /// Get the value of the first label with a name, such as `1` for
+    /// the name `priority` in `#priority:1`.
+    pub fn label_value(&self, name: &str) -> Option<String> {
+        self.labels.iter().flatten().filter(|x| x.name() == name).find_map(|x| x.value())
+    }
+
+    /// Set the value of the label with a name, or remove the label.
+    ///
+    /// The first label with the name is changed in place, in the memo
+    /// and in the labels, and any other labels with the name are removed.
+    /// A new label is added to the labels, which are written after the memo.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// // item memo is "foo #due:2026-10-20"
+    /// item.set_label_value("due", Some("2026-10-21"));
+    /// assert_eq!(item.memo, Some("foo #due:2026-10-21".into()));
+    /// item.set_label_value("due", None);
+    /// assert_eq!(item.memo, Some("foo".into()));
+    /// ```
+    ///
+    pub fn set_label_value(&mut self, name: &str, value: Option<&str>) {
+        let label = value.map(|x| Label::with_value(name, x));
+        if let Some(memo) = self.memo.as_mut() {
+            let found: Vec<_> = find_labels(memo).into_iter().filter(|(_, x)| x.name() == name).collect();
+            for (i, (range, _)) in found.into_iter().enumerate().rev() {
+                match (i, &label) {
+                    (0, Some(label)) => memo.replace_range(range, &label.to_string()),
+                    _ => remove_label_text(memo, range),
+                }
+            }
+        }
+        let mut labels: Vec<Label> = Vec::new();
+        let mut label = label;
+        for x in self.labels.take().into_iter().flatten() {
+            if x.name() != name {
+                labels.push(x);
+            } else if let Some(label) = label.take() {
+                labels.push(label);
+            }
+        }
+        labels.extend(label);
+        self.labels = if labels.is_empty() { None } else { Some(labels) };
+    }
+
+    /// Get the explicit id, from the first `#id` label, such as `venue`
+    /// for `#id:venue`; see `ItemIds` for ids of items without one.
+    pub fn id(&self) -> Option<String> {
+        self.label_value(ID_LABEL_NAME)
+    }
+
+    /// Get the due date, from the first valid `#due` label.
+    ///
+    /// This is only for absolute dates; use `due_at` for relative dates,
+    /// such as `#due:tomorrow`.
+    ///
+    pub fn due(&self) -> Option<Due> {
+        self.labels.iter().flatten()
+            .filter(|x| x.name() == DUE_LABEL_NAME)
+            .find_map(|x| Due::parse(&x.value()?))
+    }
+
+    /// Get the due date, from the first valid `#due` label,
+    /// which may be relative to now, such as `#due:tomorrow`.
+    pub fn due_at(&self, now: NaiveDateTime) -> Option<Due> {
+        self.date_label_at(DUE_LABEL_NAME, now)
+    }
+
+    /// Get the defer date, from the first valid `#defer` label,
+    /// which may be relative to now, such as `#defer:next-week`.
+    pub fn defer_at(&self, now: NaiveDateTime) -> Option<Due> {
+        self.date_label_at(DEFER_LABEL_NAME, now)
+    }
+
+    /// Get the date of the first valid label with a name,
+    /// which may be relative to now.
+    fn date_label_at(&self, name: &str, now: NaiveDateTime) -> Option<Due> {
+        self.labels.iter().flatten()
+            .filter(|x| x.name() == name)
+            .find_map(|x| Due::parse_relative(&x.value()?, now))
+    }
+
+    /// Rewrite relative dates, such as `#due:tomorrow`, to absolute
+    /// dates, such as `#due:2026-10-17`, so that their meaning does not
+    /// change as time passes.
+    ///
+    /// Each label with a relative date is rewritten in place, in the memo
+    /// and in the labels, and any other labels stay as they are.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// // item memo is "foo #due:+3d"
+    /// item.normalize_dates(now);
+    /// assert_eq!(item.memo, Some("foo #due:2026-10-19".into()));
+    /// ```
+    ///
+    pub fn normalize_dates(&mut self, now: NaiveDateTime) {
+        let normalize = |label: &Label| -> Option<Label> {
+            if !DATE_LABEL_NAMES.contains(&label.name()) {
+                return None;
+            }
+            let value = label.value().filter(|x| Due::is_relative(x))?;
+            let due = Due::parse_relative(&value, now)?;
+            Some(Label::with_value(label.name(), &due.to_string()))
+        };
+        if let Some(memo) = self.memo.as_mut() {
+            for (range, label) in find_labels(memo).into_iter().rev() {
+                if let Some(label) = normalize(&label) {
+                    memo.replace_range(range, &label.to_string());
+                }
+            }
+        }
+        for label in self.labels.iter_mut().flatten() {
+            if let Some(normalized) = normalize(label) {
+                *label = normalized;
+            }
+        }
+    }
+
+    /// Set the due date, or remove it.
+    pub fn set_due(&mut self, due: Option<Due>) {
+        self.set_label_value(DUE_LABEL_NAME, due.map(|x| x.to_string()).as_deref());
+    }
+
+    /// Is this item due on or before a date?
+    pub fn is_due_by(&self, date: NaiveDate) -> bool {
+        self.due().is_some_and(|x| x.is_by(date))
+    }
+
+    /// Compare items by due date, for sorting, with items that have
+    /// no due date after items that have one.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// items.sort_by(Item::cmp_by_due);
+    /// ```
+    ///
+    pub fn cmp_by_due(&self, other: &Self) -> std::cmp::Ordering {
+        match (self.due(), other.due()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+    }
+
+    /// Get the priority, from the first valid `#priority` label.
+    pub fn priority(&self) -> Option<Priority> {
+        self.labels.iter().flatten()
+            .filter(|x| x.name() == PRIORITY_LABEL_NAME)
+            .find_map(|x| Priority::parse(&x.value()?))
+    }
+
+    /// Get the priority, or a default priority if the item has none.
+    pub fn priority_or(&self, default: Priority) -> Priority {
+        self.priority().unwrap_or(default)
+    }
+
+    /// Set the priority, or remove it.
+    pub fn set_priority(&mut self, priority: Option<Priority>) {
+        self.set_label_value(PRIORITY_LABEL_NAME, priority.map(|x| x.to_string()).as_deref());
+    }
+
+    /// Compare items by priority, for sorting, with the most important first.
+    ///
+    /// Items without a priority have the default priority, if any,
+    /// else are after items that have a priority.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// items.sort_by(|a, b| a.cmp_by_priority(b, Priority::parse("3")));
+    /// ```
+    ///
+    pub fn cmp_by_priority(&self, other: &Self, default: Option<Priority>) -> std::cmp::Ordering {
+        match (self.priority().or(default), other.priority().or(default)) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+    }
+
+    /// Get the time estimate, from the first valid `#estimate` label.
+    pub fn estimate(&self) -> Option<Duration> {
+        self.labels.iter().flatten()
+            .filter(|x| x.name() == ESTIMATE_LABEL_NAME)
+            .find_map(|x| parse_duration(&x.value()?))
+    }
+
+    /// Get the clock entries, from the valid `#clock` labels, in order.
+    pub fn clock_entries(&self) -> Vec<ClockEntry> {
+        self.labels.iter().flatten()
+            .filter(|x| x.name() == CLOCK_LABEL_NAME)
+            .filter_map(|x| ClockEntry::parse(&x.value()?))
+            .collect()
+    }
+
+    /// Get the time spent, which is the first valid `#spent` label
+    /// plus the clock entries that are not running.
+    pub fn spent(&self) -> Duration {
+        let spent = self.labels.iter().flatten()
+            .filter(|x| x.name() == SPENT_LABEL_NAME)
+            .find_map(|x| parse_duration(&x.value()?))
+            .unwrap_or_else(Duration::zero);
+        self.clock_entries().iter().filter_map(|x| x.duration).fold(spent, saturating_add)
+    }
+
+    /// Is a clock entry running?
+    pub fn is_clocked_in(&self) -> bool {
+        self.clock_entries().iter().any(ClockEntry::is_running)
+    }
+
+    /// Start the clock, by adding a running clock entry as a new
+    /// continuation line, such as `#clock:2026-10-16T09:00`.
+    ///
+    /// Returns false if a clock entry is already running.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// // item memo is "foo"
+    /// item.clock_in(now);
+    /// assert_eq!(item.memo, Some("foo\n#clock:2026-10-16T09:00".into()));
+    /// item.clock_out(now + Duration::minutes(90));
+    /// assert_eq!(item.memo, Some("foo\n#clock:2026-10-16T09:00:+1h30m".into()));
+    /// ```
+    ///
+    pub fn clock_in(&mut self, now: NaiveDateTime) -> bool {
+        if self.is_clocked_in() {
+            return false;
+        }
+        let entry = ClockEntry { start: now.with_nanosecond(0).unwrap_or(now), duration: None };
+        let label = Label::new(std::iter::once(CLOCK_LABEL_NAME).chain(entry.to_string().split(':')));
+        let memo = self.memo.get_or_insert_with(String::new);
+        if !memo.is_empty() {
+            memo.push('\n');
+        }
+        memo.push_str(&label.to_string());
+        self.labels.get_or_insert_with(Vec::new).push(label);
+        true
+    }
+
+    /// Stop the clock, by setting the duration of the running clock entry.
+    ///
+    /// Returns the duration, or `None` if no clock entry is running.
+    ///
+    pub fn clock_out(&mut self, now: NaiveDateTime) -> Option<Duration> {
+        let (range, label) = find_labels(self.memo.as_deref().unwrap_or_default()).into_iter()
+            .filter(|(_, x)| x.name() == CLOCK_LABEL_NAME)
+            .find(|(_, x)| x.value().and_then(|x| ClockEntry::parse(&x)).is_some_and(|x| x.is_running()))?;
+        let mut entry = ClockEntry::parse(&label.value()?)?;
+        let duration = (now - entry.start).max(Duration::zero());
+        entry.duration = Some(Duration::seconds(duration.num_seconds()));
+        let stopped = Label::new(std::iter::once(CLOCK_LABEL_NAME).chain(entry.to_string().split(':')));
+        self.memo.as_mut()?.replace_range(range, &stopped.to_string());
+        if let Some(x) = self.labels.iter_mut().flatten().find(|x| **x == label) {
+            *x = stopped;
+        }
+        Some(duration)
+    }
+
+    /// Get the memo followed by any labels that are not in the memo,
+    /// such as stripped labels, each on its own line.
+    ///
+    /// This is the text to write for the item after its checkbox,
+    /// so that labels are kept when the item is written back.
+    ///
+    pub fn memo_with_labels(&self) -> String {
+        let mut s = String::from(match &self.memo { Some(x) => x, None => MEMO_DEFAULT });
+        let memo_labels = match &self.memo {
+            Some(x) => labels_from_memo(x, LabelDedup::First),
+            None => None,
+        };
+        for label in self.labels.iter().flatten() {
+            if !memo_labels.iter().flatten().any(|x| x == label) {
+                s.push('\n');
+                s.push_str(&label.to_string());
+            }
+        }
+        s
+    }
+
+}
+
+/// Remove the text of a label from memo text, with the whitespace
+/// before it, and remove its line if nothing else remains.
+fn remove_label_text(memo: &mut String, range: std::ops::Range<usize>) {
+    let line_start = memo[..range.start].rfind('\n').map_or(0, |x| x + 1);
+    let line_end = memo[range.end..].find('\n').map_or(memo.len(), |x| range.end + x);
+    if memo[line_start..range.start].trim().is_empty() && memo[range.end..line_end].trim().is_empty() {
+        // Remove the line, with the line ending before it, or else after it.
+        match line_start {
+            0 => memo.replace_range(line_start..(line_end + 1).min(memo.len()), ""),
+            _ => memo.replace_range(line_start - 1..line_end, ""),
+        }
+        return;
+    }
+    let start = range.start - memo[line_start..range.start].len() + memo[line_start..range.start].trim_end().len();
+    memo.replace_range(start..range.end, "");
 }
 
-static NEST_DEFAULT: i8 = 0;
-static LIST_MARKER_DEFAULT: &str = "";
-static LIST_MARKER_SPACER_DEFAULT: &str = "";
-static CHECKBOX_PREFIX_DEFAULT: &str = "";
-static CHECKBOX_OPEN_DEFAULT: &str = "[";
-static CHECKBOX_MARK_DEFAULT: &str = " ";
-static CHECKBOX_SHUT_DEFAULT: &str = "]";
-static CHECKBOX_SUFFIX_DEFAULT: &str = " ";
 static MEMO_DEFAULT: &str = "?";
 
+/// Write the item in the default style of `RenderOptions`,
+/// indented by its nest.
 impl std::fmt::Display for Item {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}{}{}{}{}{}{}{}",
-            LIST_MARKER_DEFAULT,
-            LIST_MARKER_SPACER_DEFAULT,
-            CHECKBOX_PREFIX_DEFAULT,
-            CHECKBOX_OPEN_DEFAULT,
-            match &self.mark { Some(x) => x, None => CHECKBOX_MARK_DEFAULT },
-            CHECKBOX_SHUT_DEFAULT,
-            CHECKBOX_SUFFIX_DEFAULT,
-            match &self.memo { Some(x) => x, None => MEMO_DEFAULT },
-        )
+        write!(f, "{}", render_item(self, &RenderOptions::default()))
     }
 }
 
@@ -40,15 +434,23 @@ mod tests {
 
     #[test]
     fn test_fmt_with_default_attributes() {
+        let item: Item = Item::default();
+        let actual: String = item.to_string();
+        let expect: String = "[ ] ?".to_string();
+        assert_eq!(actual, expect)
+    }
+
+    #[test]
+    fn test_fmt_with_note() {
         let item: Item = Item {
             nest: None,
             mark: None,
-            memo: None,
-            label1s: None,
-            label2s: None,
+            memo: Some("foo".into()),
+            labels: None,
+            ..Default::default()
         };
         let actual: String = item.to_string();
-        let expect: String = "[ ] ?".to_string();
+        let expect: String = "- foo".to_string();
         assert_eq!(actual, expect)
     }
 
@@ -58,14 +460,253 @@ mod tests {
             nest: Some(0),
             mark: Some("x".into()),
             memo: Some("foo".into()),
-            label1s: None,
-            label2s: None,
+            labels: None,
+            ..Default::default()
+        };
+        let actual: String = item.to_string();
+        let expect: String = "[x] foo".to_string();
+        assert_eq!(actual, expect)
+    }
+
+    #[test]
+    fn test_fmt_with_high_unicode_mark() {
+        let item: Item = Item {
+            nest: Some(0),
+            mark: Some(Status::from_char('✓')), // U+2713 CHECK MARK
+            memo: Some("foo".into()),
+            labels: None,
+            ..Default::default()
         };
         let actual: String = item.to_string();
         let expect: String = "[x] foo".to_string();
         assert_eq!(actual, expect)
     }
 
+    #[test]
+    fn test_fmt_with_custom_mark() {
+        let item: Item = Item {
+            nest: Some(0),
+            mark: Some(Status::Custom('?')),
+            memo: Some("foo".into()),
+            labels: None,
+            ..Default::default()
+        };
+        let actual: String = item.to_string();
+        let expect: String = "[?] foo".to_string();
+        assert_eq!(actual, expect)
+    }
+
+    #[test]
+    fn test_fmt_with_labels() {
+        let item: Item = Item {
+            nest: Some(0),
+            mark: Some("x".into()),
+            memo: Some("foo #goo".into()),
+            labels: Some(vec![
+                Label::new(["goo"]),
+                Label::new(["hoo"]),
+                Label::new(["joo", "koo", "loo"]),
+            ]),
+            ..Default::default()
+        };
+        let actual: String = item.to_string();
+        let expect: String = "[x] foo #goo\n    #hoo\n    #joo:koo:loo".to_string();
+        assert_eq!(actual, expect)
+    }
+
+    #[test]
+    fn test_fmt_with_nest() {
+        let item: Item = Item {
+            nest: Some(2),
+            mark: Some("x".into()),
+            memo: Some("foo\ngoo".into()),
+            labels: None,
+            ..Default::default()
+        };
+        let actual: String = item.to_string();
+        let expect: String = "        [x] foo\n            goo".to_string();
+        assert_eq!(actual, expect)
+    }
+
+    #[test]
+    fn test_labels_with_prefix() {
+        let item: Item = Item {
+            nest: Some(0),
+            mark: Some("x".into()),
+            memo: Some("foo".into()),
+            labels: Some(vec![
+                Label::new(["project", "website", "design"]),
+                Label::new(["project", "mobile"]),
+                Label::new(["project", "website"]),
+            ]),
+            ..Default::default()
+        };
+        let prefix = Label::new(["project", "website"]);
+        let actual = item.labels_with_prefix(&prefix);
+        let expect = [
+            Label::new(["project", "website", "design"]),
+            Label::new(["project", "website"]),
+        ];
+        assert_eq!(actual, expect.iter().collect::<Vec<&Label>>());
+        assert!(item.has_label_with_prefix(&Label::new(["project"])));
+        assert!(!item.has_label_with_prefix(&Label::new(["personal"])));
+    }
+
+    #[test]
+    fn test_set_label_value() {
+        let mut item: Item = Item {
+            memo: Some("foo #due:2026-10-20 goo\n#due:2026-10-21\n#hoo".into()),
+            labels: Some(vec![
+                Label::new(["due", "2026-10-20"]),
+                Label::new(["hoo"]),
+            ]),
+            ..Default::default()
+        };
+        item.set_label_value("due", Some("2026-10-22T17:00"));
+        assert_eq!(item.memo, Some("foo #due:2026-10-22T17:00 goo\n#hoo".into()));
+        assert_eq!(item.labels, Some(vec![
+            Label::new(["due", "2026-10-22T17", "00"]),
+            Label::new(["hoo"]),
+        ]));
+        item.set_label_value("due", None);
+        assert_eq!(item.memo, Some("foo goo\n#hoo".into()));
+        assert_eq!(item.labels, Some(vec![Label::new(["hoo"])]));
+        item.set_label_value("hoo", None);
+        assert_eq!(item.memo, Some("foo goo".into()));
+        assert_eq!(item.labels, None);
+        item.set_label_value("joo", Some("1"));
+        assert_eq!(item.memo, Some("foo goo".into()));
+        assert_eq!(item.labels, Some(vec![Label::new(["joo", "1"])]));
+        assert_eq!(item.label_value("joo"), Some("1".into()));
+        item.set_label_value("joo", Some("two words"));
+        assert_eq!(item.label_value("joo"), Some("two words".into()));
+        assert_eq!(item.memo_with_labels(), "foo goo\n#joo:\"two words\"");
+    }
+
+    #[test]
+    fn test_due() {
+        let item = |memo: &str| Item {
+            labels: labels_from_memo(memo, LabelDedup::First),
+            memo: Some(memo.into()),
+            ..Default::default()
+        };
+        let date = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+        let mut items = [
+            item("foo"),
+            item("goo #due:2026-10-21"),
+            item("hoo #due:2026-02-30 #due:2026-10-20T17:00"),
+        ];
+        assert_eq!(items[2].due(), Due::parse("2026-10-20T17:00"));
+        assert!(items[1].is_due_by(date(21)));
+        assert!(!items[1].is_due_by(date(20)));
+        assert!(!items[0].is_due_by(date(31)));
+        items.sort_by(Item::cmp_by_due);
+        let memos: Vec<_> = items.iter().map(|x| x.memo.clone().unwrap()).collect();
+        assert_eq!(memos, vec!["hoo #due:2026-02-30 #due:2026-10-20T17:00", "goo #due:2026-10-21", "foo"]);
+        items[2].set_due(Due::parse("2026-10-19"));
+        assert_eq!(items[2].to_string(), "[ ] foo\n    #due:2026-10-19");
+    }
+
+    #[test]
+    fn test_priority() {
+        let item = |memo: &str| Item {
+            labels: labels_from_memo(memo, LabelDedup::First),
+            memo: Some(memo.into()),
+            ..Default::default()
+        };
+        let mut items = [
+            item("foo"),
+            item("goo #priority:B"),
+            item("hoo #priority:high #priority:1"),
+        ];
+        assert_eq!(items[0].priority(), None);
+        assert_eq!(items[0].priority_or(Priority::Number(3)), Priority::Number(3));
+        assert_eq!(items[2].priority(), Some(Priority::Number(1)));
+        items.sort_by(|a, b| a.cmp_by_priority(b, None));
+        let memos: Vec<_> = items.iter().map(|x| x.memo.clone().unwrap()).collect();
+        assert_eq!(memos, vec!["hoo #priority:high #priority:1", "goo #priority:B", "foo"]);
+        items.sort_by(|a, b| a.cmp_by_priority(b, Priority::parse("A")));
+        let memos: Vec<_> = items.iter().map(|x| x.memo.clone().unwrap()).collect();
+        assert_eq!(memos, vec!["hoo #priority:high #priority:1", "foo", "goo #priority:B"]);
+        items[1].set_priority(Priority::parse("2"));
+        assert_eq!(items[1].to_string(), "[ ] foo\n    #priority:2");
+    }
+
+    #[test]
+    fn test_effort() {
+        let memo = "foo #estimate:1h30m #spent:20m\n#clock:2026-10-16T09:00:+10m";
+        let mut item = Item {
+            labels: labels_from_memo(memo, LabelDedup::First),
+            memo: Some(memo.into()),
+            ..Default::default()
+        };
+        let now = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap().and_hms_opt(10, 0, 0).unwrap();
+        assert_eq!(item.estimate(), Some(Duration::minutes(90)));
+        assert_eq!(item.spent(), Duration::minutes(30));
+        assert_eq!(item.clock_out(now), None);
+        assert!(item.clock_in(now));
+        assert!(item.is_clocked_in());
+        assert!(!item.clock_in(now));
+        assert_eq!(item.clock_out(now + Duration::minutes(45)), Some(Duration::minutes(45)));
+        assert!(!item.is_clocked_in());
+        assert_eq!(item.spent(), Duration::minutes(75));
+        assert_eq!(item.memo_with_labels(), "foo #estimate:1h30m #spent:20m\n#clock:2026-10-16T09:00:+10m\n#clock:2026-10-16T10:00:+45m");
+    }
+
+    #[test]
+    fn test_normalize_dates() {
+        let memo = "foo #due:+3d #defer:tomorrow\n#due:fri";
+        let mut item = Item {
+            labels: labels_from_memo(memo, LabelDedup::First),
+            memo: Some(memo.into()),
+            ..Default::default()
+        };
+        let now = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        assert_eq!(item.due(), None);
+        assert_eq!(item.due_at(now), Due::parse("2026-10-19"));
+        assert_eq!(item.defer_at(now), Due::parse("2026-10-17"));
+        item.normalize_dates(now);
+        assert_eq!(item.memo, Some("foo #due:2026-10-19 #defer:2026-10-17\n#due:2026-10-16".into()));
+        assert_eq!(item.due(), Due::parse("2026-10-19"));
+        let unchanged = item.clone();
+        item.normalize_dates(now);
+        assert_eq!(item, unchanged);
+    }
+
+    #[test]
+    fn test_normalize_dates_with_other_labels() {
+        let now = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        for (memo, expect) in [
+            ("foo #due:soon #due:tomorrow", "foo #due:soon #due:2026-10-17"),
+            ("foo #due:tomorrow #due:2026-10-20", "foo #due:2026-10-17 #due:2026-10-20"),
+        ] {
+            let mut item = Item {
+                labels: labels_from_memo(memo, LabelDedup::Keep),
+                memo: Some(memo.into()),
+                ..Default::default()
+            };
+            let due = item.due_at(now);
+            item.normalize_dates(now);
+            assert_eq!(item.memo.as_deref(), Some(expect));
+            assert_eq!(item.labels, labels_from_memo(expect, LabelDedup::Keep));
+            assert_eq!(item.due(), due);
+        }
+    }
+
+    #[test]
+    fn test_serde_json_with_labels() {
+        let item: Item = Item {
+            nest: Some(0),
+            mark: Some("x".into()),
+            memo: Some("foo #goo:hoo".into()),
+            labels: Some(vec![Label::new(["goo", "hoo"])]),
+            ..Default::default()
+        };
+        let json: String = serde_json::to_string(&item).expect("json");
+        assert_eq!(json, r#"{"nest":0,"mark":"x","memo":"foo #goo:hoo","labels":[["goo","hoo"]]}"#);
+        let actual: Item = serde_json::from_str(&json).expect("actual");
+        assert_eq!(actual, item);
+    }
 
     #[test]
     fn test_serde_json_from_str() {
@@ -81,8 +722,8 @@ mod tests {
             nest: Some(0),
             mark: Some("x".into()),
             memo: Some("foo".into()),
-            label1s: None,
-            label2s: None,
+            labels: None,
+            ..Default::default()
         };
         assert_eq!(actual, expect);
     }
@@ -93,11 +734,11 @@ mod tests {
             nest: Some(0),
             mark: Some("x".into()),
             memo: Some("foo".into()),
-            label1s: None,
-            label2s: None,
+            labels: None,
+            ..Default::default()
         };
         let actual: String = serde_json::to_string(&item).expect("actual");
-        let expect: String = r#"{"nest":0,"mark":"x","memo":"foo","label1s":null,"label2s":null}"#.to_string();
+        let expect: String = r#"{"nest":0,"mark":"x","memo":"foo","labels":null}"#.to_string();
         assert_eq!(actual, expect);
     }
 
@@ -0,0 +1,350 @@
+use crate::item::item::Item;
+
+/// Index of a node in an item tree.
+pub type NodeId = usize;
+
+/// A node in an item tree.
+#[derive(Debug, PartialEq)]
+pub struct Node {
+    pub item: Item,
+    pub parent: Option<NodeId>,
+    pub children: Vec<NodeId>,
+    /// Depth in the tree, where roots are 0.
+    pub depth: usize,
+}
+
+/// A tree of items, built from the nest levels of a flat list.
+///
+/// The parent of an item is the nearest preceding item that has a
+/// smaller nest level. This means that a jump in nesting, such as
+/// from 0 to 2, makes a child at depth 1, and every item without a
+/// parent is a root, at any nest level, such as a first item at 2.
+///
+/// The nodes are stored in an arena in the order of the flat list,
+/// and refer to each other by their index.
+///
+/// Example:
+///
+/// ```
+/// let items = load_items_via_str("[ ] a\n    [ ] b\n[ ] c\n").unwrap();
+/// let tree = ItemTree::from_items(items);
+/// assert_eq!(tree.roots, vec![0, 2]);
+/// assert_eq!(tree[1].parent, Some(0));
+/// ```
+///
+#[derive(Debug, Default, PartialEq)]
+pub struct ItemTree {
+    pub nodes: Vec<Node>,
+    pub roots: Vec<NodeId>,
+}
+
+impl ItemTree {
+
+    /// Build a tree from a flat list of items.
+    pub fn from_items(items: Vec<Item>) -> Self {
+        let mut tree = ItemTree::default();
+        // Stack of (nest, id) for the current path from a root.
+        let mut stack: Vec<(u8, NodeId)> = Vec::new();
+        for item in items {
+            let nest = item.nest.unwrap_or(0);
+            while stack.last().is_some_and(|(x, _)| *x >= nest) {
+                stack.pop();
+            }
+            let id = tree.nodes.len();
+            let parent = stack.last().map(|(_, x)| *x);
+            match parent {
+                Some(parent) => tree.nodes[parent].children.push(id),
+                None => tree.roots.push(id),
+            }
+            tree.nodes.push(Node {
+                item,
+                parent,
+                children: Vec::new(),
+                depth: stack.len(),
+            });
+            stack.push((nest, id));
+        }
+        tree
+    }
+
+    /// Convert the tree back to a flat list of items, in depth-first order.
+    ///
+    /// The nest level of each item is kept as-is; use `normalize_nest`
+    /// first to set each nest level to the depth in the tree.
+    ///
+    pub fn into_items(self) -> Vec<Item> {
+        let order: Vec<NodeId> = self.depth_first().collect();
+        let mut items: Vec<Option<Item>> = self.nodes.into_iter().map(|x| Some(x.item)).collect();
+        order.into_iter().filter_map(|id| items[id].take()).collect()
+    }
+
+    /// Set the nest level of each item to its depth in the tree,
+    /// which removes any jumps in nesting.
+    pub fn normalize_nest(&mut self) {
+        for node in self.nodes.iter_mut() {
+            node.item.nest = Some(u8::try_from(node.depth).unwrap_or(u8::MAX));
+        }
+    }
+
+    /// Sort each group of siblings, including the roots, with a comparator
+    /// of items, so that each item keeps its parent and children.
+    ///
+    /// The sort is stable, so items that are equal stay in order.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// tree.sort_siblings_by(Item::cmp_by_due);
+    /// ```
+    ///
+    pub fn sort_siblings_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&Item, &Item) -> std::cmp::Ordering,
+    {
+        let nodes = &self.nodes;
+        self.roots.sort_by(|a, b| compare(&nodes[*a].item, &nodes[*b].item));
+        for id in 0..self.nodes.len() {
+            let mut children = std::mem::take(&mut self.nodes[id].children);
+            children.sort_by(|a, b| compare(&self.nodes[*a].item, &self.nodes[*b].item));
+            self.nodes[id].children = children;
+        }
+    }
+
+    /// Get a node.
+    pub fn get(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(id)
+    }
+
+    /// Get a node as mutable.
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(id)
+    }
+
+    /// Iterate over all nodes, depth-first, starting with the roots in order.
+    pub fn depth_first(&self) -> DepthFirst<'_> {
+        DepthFirst {
+            tree: self,
+            stack: self.roots.iter().rev().copied().collect(),
+        }
+    }
+
+    /// Iterate over the descendants of a node, depth-first, not including the node.
+    pub fn descendants(&self, id: NodeId) -> DepthFirst<'_> {
+        DepthFirst {
+            tree: self,
+            stack: self.nodes[id].children.iter().rev().copied().collect(),
+        }
+    }
+
+    /// Iterate over the ancestors of a node, starting with its parent.
+    pub fn ancestors(&self, id: NodeId) -> Ancestors<'_> {
+        Ancestors {
+            tree: self,
+            next: self.nodes[id].parent,
+        }
+    }
+
+    /// Iterate over the siblings of a node, in order, not including the node.
+    pub fn siblings(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        let group = match self.nodes[id].parent {
+            Some(parent) => &self.nodes[parent].children,
+            None => &self.roots,
+        };
+        group.iter().copied().filter(move |x| *x != id)
+    }
+
+}
+
+impl std::ops::Index<NodeId> for ItemTree {
+    type Output = Node;
+    fn index(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+}
+
+impl std::ops::IndexMut<NodeId> for ItemTree {
+    fn index_mut(&mut self, id: NodeId) -> &mut Node {
+        &mut self.nodes[id]
+    }
+}
+
+/// Depth-first iterator over node ids.
+pub struct DepthFirst<'a> {
+    tree: &'a ItemTree,
+    stack: Vec<NodeId>,
+}
+
+impl Iterator for DepthFirst<'_> {
+    type Item = NodeId;
+    fn next(&mut self) -> Option<NodeId> {
+        let id = self.stack.pop()?;
+        self.stack.extend(self.tree.nodes[id].children.iter().rev());
+        Some(id)
+    }
+}
+
+/// Ancestors iterator over node ids.
+pub struct Ancestors<'a> {
+    tree: &'a ItemTree,
+    next: Option<NodeId>,
+}
+
+impl Iterator for Ancestors<'_> {
+    type Item = NodeId;
+    fn next(&mut self) -> Option<NodeId> {
+        let id = self.next?;
+        self.next = self.tree.nodes[id].parent;
+        Some(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(nest: u8, memo: &str) -> Item {
+        Item {
+            nest: Some(nest),
+            mark: Some(" ".into()),
+            memo: Some(memo.into()),
+            labels: None,
+            ..Default::default()
+        }
+    }
+
+    fn memos(tree: &ItemTree, ids: impl Iterator<Item = NodeId>) -> Vec<String> {
+        ids.map(|id| tree[id].item.memo.clone().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_from_items() {
+        let tree = ItemTree::from_items(vec![
+            item(0, "a"),
+            item(1, "a1"),
+            item(1, "a2"),
+            item(2, "a2i"),
+            item(0, "b"),
+        ]);
+        assert_eq!(tree.roots, vec![0, 4]);
+        assert_eq!(tree[0].children, vec![1, 2]);
+        assert_eq!(tree[2].children, vec![3]);
+        assert_eq!(tree[3].parent, Some(2));
+        assert_eq!(tree[3].depth, 2);
+        assert_eq!(tree[4].parent, None);
+    }
+
+    #[test]
+    fn test_from_items_with_jump() {
+        let tree = ItemTree::from_items(vec![
+            item(0, "a"),
+            item(2, "a1"),
+            item(1, "a2"),
+            item(0, "b"),
+        ]);
+        assert_eq!(tree[1].parent, Some(0));
+        assert_eq!(tree[1].depth, 1);
+        assert_eq!(tree[2].parent, Some(0));
+        assert_eq!(tree[2].depth, 1);
+        assert_eq!(tree.roots, vec![0, 3]);
+    }
+
+    #[test]
+    fn test_from_items_with_nested_first_item() {
+        let tree = ItemTree::from_items(vec![
+            item(2, "a"),
+            item(0, "b"),
+            item(1, "b1"),
+        ]);
+        assert_eq!(tree.roots, vec![0, 1]);
+        assert_eq!(tree[0].depth, 0);
+        assert_eq!(tree[2].parent, Some(1));
+    }
+
+    #[test]
+    fn test_depth_first() {
+        let mut tree = ItemTree::from_items(vec![
+            item(0, "a"),
+            item(1, "a1"),
+            item(2, "a1i"),
+            item(1, "a2"),
+            item(0, "b"),
+        ]);
+        assert_eq!(memos(&tree, tree.depth_first()), vec!["a", "a1", "a1i", "a2", "b"]);
+        tree[0].children.reverse();
+        assert_eq!(memos(&tree, tree.depth_first()), vec!["a", "a2", "a1", "a1i", "b"]);
+        assert_eq!(memos(&tree, tree.descendants(0)), vec!["a2", "a1", "a1i"]);
+    }
+
+    #[test]
+    fn test_ancestors() {
+        let tree = ItemTree::from_items(vec![
+            item(0, "a"),
+            item(1, "a1"),
+            item(2, "a1i"),
+        ]);
+        assert_eq!(memos(&tree, tree.ancestors(2)), vec!["a1", "a"]);
+        assert_eq!(tree.ancestors(0).count(), 0);
+    }
+
+    #[test]
+    fn test_siblings() {
+        let tree = ItemTree::from_items(vec![
+            item(0, "a"),
+            item(1, "a1"),
+            item(1, "a2"),
+            item(1, "a3"),
+            item(0, "b"),
+        ]);
+        assert_eq!(memos(&tree, tree.siblings(2)), vec!["a1", "a3"]);
+        assert_eq!(memos(&tree, tree.siblings(0)), vec!["b"]);
+    }
+
+    #[test]
+    fn test_sort_siblings_by() {
+        let mut tree = ItemTree::from_items(vec![
+            item(0, "b"),
+            item(1, "b2"),
+            item(1, "b1"),
+            item(2, "b1ii"),
+            item(2, "b1i"),
+            item(0, "a"),
+        ]);
+        tree.sort_siblings_by(|a, b| a.memo.cmp(&b.memo));
+        assert_eq!(memos(&tree, tree.depth_first()), vec!["a", "b", "b1", "b1i", "b1ii", "b2"]);
+    }
+
+    #[test]
+    fn test_into_items() {
+        let items = vec![
+            item(0, "a"),
+            item(2, "a1"),
+            item(0, "b"),
+        ];
+        let tree = ItemTree::from_items(items);
+        let actual = tree.into_items();
+        let expect = vec![
+            item(0, "a"),
+            item(2, "a1"),
+            item(0, "b"),
+        ];
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn test_into_items_with_normalize_nest() {
+        let mut tree = ItemTree::from_items(vec![
+            item(0, "a"),
+            item(2, "a1"),
+            item(0, "b"),
+        ]);
+        tree.normalize_nest();
+        let actual = tree.into_items();
+        let expect = vec![
+            item(0, "a"),
+            item(1, "a1"),
+            item(0, "b"),
+        ];
+        assert_eq!(actual, expect);
+    }
+
+}
@@ -0,0 +1,472 @@
+use std::ops::Range;
+use serde::{Deserialize, Deserializer, Serialize};
+use crate::item::item_parser;
+
+/// A label, such as `#foo` or `#project:website:design`.
+///
+/// A label is a path of 1 or more phrases. A label with 2 phrases is
+/// often used as a key and a value, such as `#priority:1`.
+///
+/// Example:
+///
+/// ```
+/// let label = Label::parse("#project:website:design").unwrap();
+/// assert_eq!(label.path(), ["project", "website", "design"]);
+/// assert!(label.starts_with(&Label::new(["project", "website"])));
+/// assert_eq!(label.to_string(), "#project:website:design");
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(transparent)]
+pub struct Label {
+    /// The phrases, which are 1 or more, so that a label has a name.
+    path: Vec<String>,
+}
+
+impl Label {
+
+    /// Create a label from its phrases.
+    ///
+    /// Panics if there are no phrases, because every label has a name.
+    ///
+    pub fn new<S: Into<String>>(path: impl IntoIterator<Item = S>) -> Self {
+        let path: Vec<String> = path.into_iter().map(Into::into).collect();
+        assert!(!path.is_empty(), "a label has 1 or more phrases");
+        Label { path }
+    }
+
+    /// Get the phrases, such as `priority` and `1` in `#priority:1`.
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// Create a label from a name and a value, such as `#priority:1`.
+    ///
+    /// A value that has splitters, such as `2026-10-20T17:00`, is split
+    /// into phrases as it would be parsed, so that the label has the value,
+    /// and any other value is one phrase, which is written quoted.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// assert_eq!(Label::with_value("due", "2026-10-20T17:00").path(), ["due", "2026-10-20T17", "00"]);
+    /// assert_eq!(Label::with_value("note", "two words").to_string(), r#"#note:"two words""#);
+    /// ```
+    ///
+    pub fn with_value(name: &str, value: &str) -> Self {
+        let split = Label::new(std::iter::once(name).chain(value.split(':')));
+        if split.path.iter().all(|x| is_bare_phrase(x)) {
+            split
+        } else {
+            Label::new([name, value])
+        }
+    }
+
+    /// Parse a label from text, with or without its label open.
+    ///
+    /// Returns `None` unless the whole text is one label.
+    ///
+    pub fn parse(s: &str) -> Option<Label> {
+        let s = s.trim();
+        let input = if s.starts_with(['#', '＃']) { s.to_string() } else { format!("#{}", s) };
+        match item_parser::label(&input) {
+            Ok(("", (_, phrases))) => Some(Label::new(phrases)),
+            _ => None,
+        }
+    }
+
+    /// Get the first phrase, such as `priority` in `#priority:1`.
+    pub fn name(&self) -> &str {
+        &self.path[0]
+    }
+
+    /// Get the phrases after the first, joined by the splitter,
+    /// such as `1` in `#priority:1`, or `None` if there is only 1 phrase.
+    pub fn value(&self) -> Option<String> {
+        if self.path.len() > 1 { Some(self.path[1..].join(":")) } else { None }
+    }
+
+    /// Is this label the same as the prefix, or under the prefix?
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let prefix = Label::new(["project", "website"]);
+    /// assert!(Label::new(["project", "website"]).starts_with(&prefix));
+    /// assert!(Label::new(["project", "website", "design"]).starts_with(&prefix));
+    /// assert!(!Label::new(["project"]).starts_with(&prefix));
+    /// ```
+    ///
+    pub fn starts_with(&self, prefix: &Label) -> bool {
+        self.path.starts_with(&prefix.path)
+    }
+
+}
+
+/// Is a phrase written as-is in a label, without quotes?
+fn is_bare_phrase(phrase: &str) -> bool {
+    !phrase.is_empty() && item_parser::label_phrase(phrase) == Ok(("", phrase))
+}
+
+/// Write a phrase as-is, or quoted with escapes if it is not bare.
+fn write_phrase(f: &mut std::fmt::Formatter, phrase: &str) -> std::fmt::Result {
+    if is_bare_phrase(phrase) {
+        return write!(f, "{}", phrase);
+    }
+    write!(f, "\"")?;
+    for c in phrase.chars() {
+        if c == '"' || c == '\\' {
+            write!(f, "\\")?;
+        }
+        write!(f, "{}", c)?;
+    }
+    write!(f, "\"")
+}
+
+/// Write the label with its phrases quoted as needed, so that it parses
+/// back to the same label.
+impl std::fmt::Display for Label {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "#")?;
+        for (i, phrase) in self.path.iter().enumerate() {
+            if i > 0 {
+                write!(f, ":")?;
+            }
+            write_phrase(f, phrase)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deserialize a label from its phrases, which must not be empty,
+/// because every label has a name.
+impl<'de> Deserialize<'de> for Label {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let path = Vec::<String>::deserialize(deserializer)?;
+        if path.is_empty() {
+            return Err(serde::de::Error::invalid_length(0, &"a label with 1 or more phrases"));
+        }
+        Ok(Label { path })
+    }
+}
+
+/// Policy for labels that appear more than once in an item.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum LabelDedup {
+    /// Keep every occurrence, in source order.
+    Keep,
+    /// Keep the first occurrence of each label.
+    #[default]
+    First,
+    /// Keep the last occurrence of each label.
+    Last,
+}
+
+impl LabelDedup {
+
+    /// Apply the policy to labels that are in source order.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let labels = vec!["a", "b", "a"];
+    /// assert_eq!(LabelDedup::First.apply(labels.clone()), vec!["a", "b"]);
+    /// assert_eq!(LabelDedup::Last.apply(labels.clone()), vec!["b", "a"]);
+    /// ```
+    ///
+    pub fn apply<T: PartialEq>(&self, labels: Vec<T>) -> Vec<T> {
+        match self {
+            LabelDedup::Keep => labels,
+            LabelDedup::First => {
+                let mut vec: Vec<T> = Vec::new();
+                for label in labels {
+                    if !vec.contains(&label) {
+                        vec.push(label);
+                    }
+                }
+                vec
+            },
+            LabelDedup::Last => {
+                let mut vec: Vec<T> = Vec::new();
+                for label in labels.into_iter().rev() {
+                    if !vec.contains(&label) {
+                        vec.push(label);
+                    }
+                }
+                vec.reverse();
+                vec
+            },
+        }
+    }
+
+}
+
+/// Find labels in text, in source order, with their byte ranges.
+///
+/// A label must be at the start of the text or after whitespace,
+/// so that text such as `issue#1` is not a label. Text in fenced code
+/// blocks has no labels.
+///
+/// Example:
+///
+/// ```
+/// let found = find_labels("foo #personal #priority:1");
+/// assert_eq!(found, vec![
+///     (4..13, Label::new(["personal"])),
+///     (14..25, Label::new(["priority", "1"])),
+/// ]);
+/// ```
+///
+pub fn find_labels(text: &str) -> Vec<(Range<usize>, Label)> {
+    let mut vec = Vec::new();
+    for (offset, line, is_code) in item_parser::code_lines(text) {
+        if is_code {
+            continue;
+        }
+        let mut prev: Option<char> = None;
+        let mut skip_to = 0;
+        for (i, c) in line.char_indices() {
+            if i >= skip_to && prev.is_none_or(char::is_whitespace) {
+                if let Ok((rest, (_, phrases))) = item_parser::label(&line[i..]) {
+                    let len = line.len() - i - rest.len();
+                    vec.push((offset + i..offset + i + len, Label::new(phrases)));
+                    skip_to = i + len;
+                }
+            }
+            prev = Some(c);
+        }
+    }
+    vec
+}
+
+/// Get the labels of memo text, as an item field.
+///
+/// Returns the labels in source order, with the dedup policy applied,
+/// or `None` if the memo has no labels.
+///
+/// Example:
+///
+/// ```
+/// let labels = labels_from_memo("#personal #priority:1", LabelDedup::First);
+/// assert_eq!(labels, Some(vec![
+///     Label::new(["personal"]),
+///     Label::new(["priority", "1"]),
+/// ]));
+/// ```
+///
+pub fn labels_from_memo(memo: &str, dedup: LabelDedup) -> Option<Vec<Label>> {
+    let labels: Vec<Label> = find_labels(memo).into_iter().map(|(_, label)| label).collect();
+    let labels = dedup.apply(labels);
+    if labels.is_empty() { None } else { Some(labels) }
+}
+
+/// Strip labels from memo text.
+///
+/// Lines without labels are kept as-is. Lines with labels have
+/// the labels removed and their whitespace tidied, and are dropped
+/// if nothing else remains.
+///
+/// Example:
+///
+/// ```
+/// let memo = "Call friends\n#personal\n#priority:1";
+/// assert_eq!(strip_labels(memo), "Call friends");
+/// ```
+///
+pub fn strip_labels(memo: &str) -> String {
+    let found = find_labels(memo);
+    let mut lines: Vec<String> = Vec::new();
+    let mut offset = 0;
+    for line in memo.split('\n') {
+        let line_range = offset..offset + line.len();
+        offset += line.len() + 1;
+        let found: Vec<&Range<usize>> = found.iter()
+            .map(|(range, _)| range)
+            .filter(|x| line_range.contains(&x.start))
+            .collect();
+        if found.is_empty() {
+            lines.push(line.to_string());
+            continue;
+        }
+        let mut s = String::new();
+        let mut start = line_range.start;
+        for range in found {
+            s.push_str(&memo[start..range.start]);
+            start = range.end;
+        }
+        s.push_str(&memo[start..line_range.end]);
+        // Keep the indentation of the line, relative to the memo.
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let s = s.split_whitespace().collect::<Vec<&str>>().join(" ");
+        if !s.is_empty() {
+            lines.push(format!("{}{}", indent, s));
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label_parse() {
+        assert_eq!(Label::parse("#foo"), Some(Label::new(["foo"])));
+        assert_eq!(Label::parse("foo:goo:hoo"), Some(Label::new(["foo", "goo", "hoo"])));
+        assert_eq!(Label::parse("＃foo：goo"), Some(Label::new(["foo", "goo"]))); // U+FF03 FULLWIDTH NUMBER SIGN, U+FF1A FULLWIDTH COLON
+        assert_eq!(Label::parse("#foo goo"), None);
+        assert_eq!(Label::parse(""), None);
+    }
+
+    #[test]
+    fn test_label_name_and_value() {
+        let label = Label::new(["foo", "goo", "hoo"]);
+        assert_eq!(label.name(), "foo");
+        assert_eq!(label.value(), Some("goo:hoo".into()));
+        assert_eq!(Label::new(["foo"]).value(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "a label has 1 or more phrases")]
+    fn test_label_new_without_phrases() {
+        Label::new(Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_label_starts_with() {
+        let prefix = Label::new(["project", "website"]);
+        assert!(Label::new(["project", "website"]).starts_with(&prefix));
+        assert!(Label::new(["project", "website", "design"]).starts_with(&prefix));
+        assert!(!Label::new(["project", "websites"]).starts_with(&prefix));
+        assert!(!Label::new(["project"]).starts_with(&prefix));
+    }
+
+    #[test]
+    fn test_label_fmt() {
+        assert_eq!(Label::new(["foo", "goo", "hoo"]).to_string(), "#foo:goo:hoo");
+        assert_eq!(Label::new(["ver", "1.2.3"]).to_string(), "#ver:1.2.3");
+    }
+
+    #[test]
+    fn test_label_fmt_with_quotes() {
+        assert_eq!(Label::new(["note", "two words"]).to_string(), r#"#note:"two words""#);
+        assert_eq!(Label::new(["note", r#"say "hi" \ bye"#]).to_string(), r#"#note:"say \"hi\" \\ bye""#);
+        assert_eq!(Label::new(["note", "end."]).to_string(), r#"#note:"end.""#);
+        assert_eq!(Label::new(["note", "a:b"]).to_string(), r#"#note:"a:b""#);
+        for label in [
+            Label::new(["note", "two words"]),
+            Label::new(["note", r#"say "hi" \ bye"#]),
+            Label::new(["note", "end."]),
+            Label::new(["note", "a:b"]),
+        ] {
+            assert_eq!(Label::parse(&label.to_string()), Some(label));
+        }
+    }
+
+    #[test]
+    fn test_label_with_value() {
+        assert_eq!(Label::with_value("priority", "1"), Label::new(["priority", "1"]));
+        assert_eq!(Label::with_value("url", "https://example.com"), Label::new(["url", "https", "//example.com"]));
+        assert_eq!(Label::with_value("note", "two words"), Label::new(["note", "two words"]));
+        assert_eq!(Label::with_value("note", "a: b"), Label::new(["note", "a: b"]));
+        for value in ["1", "2026-10-20T17:00", "https://example.com", "two words", "a: b", "a::b"] {
+            assert_eq!(Label::parse(&Label::with_value("foo", value).to_string()).unwrap().value(), Some(value.into()));
+        }
+    }
+
+    #[test]
+    fn test_label_serde_json() {
+        let label = Label::new(["foo", "goo"]);
+        assert_eq!(serde_json::to_string(&label).unwrap(), r#"["foo","goo"]"#);
+        assert_eq!(serde_json::from_str::<Label>(r#"["foo","goo"]"#).unwrap(), label);
+    }
+
+    #[test]
+    fn test_label_serde_json_with_empty_path() {
+        assert!(serde_json::from_str::<Label>("[]").is_err());
+        assert!(serde_json::from_str::<crate::item::item::Item>(r#"{"labels":[["foo"]]}"#).is_ok());
+        assert!(serde_json::from_str::<crate::item::item::Item>(r#"{"labels":[[]]}"#).is_err());
+    }
+
+    #[test]
+    fn test_find_labels() {
+        let actual = find_labels("#foo bar #goo:hoo #joo:koo:loo");
+        let expect = vec![
+            (0..4, Label::new(["foo"])),
+            (9..17, Label::new(["goo", "hoo"])),
+            (18..30, Label::new(["joo", "koo", "loo"])),
+        ];
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn test_find_labels_with_high_unicode() {
+        let actual = find_labels("＃αβ"); // U+FF03 FULLWIDTH NUMBER SIGN, U+03B1 GREEK SMALL LETTER ALPHA, U+03B2 GREEK SMALL LETTER BETA
+        let expect = vec![
+            (0..7, Label::new(["αβ"])),
+        ];
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn test_find_labels_with_punctuation_and_quotes() {
+        let actual = find_labels(r#"Call #phone:1-800-FLOWERS, see #url:https://example.com. Ask #who:"Alice B" #ver:1.2.3"#);
+        let expect = vec![
+            (5..25, Label::new(["phone", "1-800-FLOWERS"])),
+            (31..55, Label::new(["url", "https", "//example.com"])),
+            (61..75, Label::new(["who", "Alice B"])),
+            (76..86, Label::new(["ver", "1.2.3"])),
+        ];
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn test_find_labels_ignores_fenced_code() {
+        let actual = find_labels("#a\n```\n#b\n```\n#c");
+        assert_eq!(actual, vec![
+            (0..2, Label::new(["a"])),
+            (14..16, Label::new(["c"])),
+        ]);
+    }
+
+    #[test]
+    fn test_strip_labels_keeps_indentation_and_fenced_code() {
+        assert_eq!(strip_labels("foo #a\n  goo  #b\n```\n#c\n```"), "foo\n  goo\n```\n#c\n```");
+    }
+
+    #[test]
+    fn test_find_labels_ignores_mid_word_and_empty() {
+        assert_eq!(find_labels("issue#1 # #: #foo:"), vec![
+            (13..17, Label::new(["foo"])),
+        ]);
+    }
+
+    #[test]
+    fn test_label_dedup() {
+        let labels = vec!["a", "b", "a", "c", "b"];
+        assert_eq!(LabelDedup::Keep.apply(labels.clone()), vec!["a", "b", "a", "c", "b"]);
+        assert_eq!(LabelDedup::First.apply(labels.clone()), vec!["a", "b", "c"]);
+        assert_eq!(LabelDedup::Last.apply(labels), vec!["a", "c", "b"]);
+    }
+
+    #[test]
+    fn test_labels_from_memo() {
+        let actual = labels_from_memo("foo #a\n#b:c #a #b:c", LabelDedup::Keep);
+        let expect = Some(vec![
+            Label::new(["a"]),
+            Label::new(["b", "c"]),
+            Label::new(["a"]),
+            Label::new(["b", "c"]),
+        ]);
+        assert_eq!(actual, expect);
+        assert_eq!(labels_from_memo("foo", LabelDedup::First), None);
+    }
+
+    #[test]
+    fn test_strip_labels() {
+        assert_eq!(strip_labels("foo #bar goo"), "foo goo");
+        assert_eq!(strip_labels("foo\n#bar #goo:hoo\n  goo  "), "foo\n  goo  ");
+        assert_eq!(strip_labels("#bar"), "");
+    }
+
+}
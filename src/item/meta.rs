@@ -0,0 +1,370 @@
+use chrono::format::{Item as FormatItem, StrftimeItems};
+use chrono::NaiveDate;
+use crate::item::diagnostic::{Diagnostic, Severity};
+use crate::item::item::Item;
+use crate::item::item_parser;
+use crate::item::label::Label;
+use crate::item::status::Status;
+
+/// Front matter key for the title of the list, such as `title: Party`.
+pub static TITLE_KEY: &str = "title";
+
+/// Front matter key for the owner of the list, such as `owner: Alice`.
+pub static OWNER_KEY: &str = "owner";
+
+/// Front matter key for labels that apply to all items,
+/// such as `labels: #work #client:acme`.
+pub static LABELS_KEY: &str = "labels";
+
+/// Front matter key for the checkbox marks that the list uses,
+/// such as `marks: [ ] [x] [!] [?]`.
+pub static MARKS_KEY: &str = "marks";
+
+/// Front matter key for the date format of the list, in the
+/// strftime style, such as `date_format: %d/%m/%Y`.
+pub static DATE_FORMAT_KEY: &str = "date_format";
+
+/// Date format when a list does not have one.
+pub static DATE_FORMAT_DEFAULT: &str = "%Y-%m-%d";
+
+/// Metadata of a list, from the front matter at the start of its file,
+/// which is `key: value` lines between `---` lines.
+///
+/// The fields are kept in order, including fields with keys that
+/// are not known, and the typed getters read the known keys.
+///
+/// Example:
+///
+/// ```
+/// let meta = ListMeta::parse("---\ntitle: Party\nlabels: #personal\n---\n");
+/// assert_eq!(meta.title(), Some("Party"));
+/// assert_eq!(meta.labels(), vec![Label::new(["personal"])]);
+/// ```
+///
+#[derive(Debug, Default, Clone)]
+pub struct ListMeta {
+    /// Keys and values, in order.
+    pub fields: Vec<(String, String)>,
+    /// The front matter text as it was parsed, with its fields,
+    /// to know whether it has changed.
+    pub loaded: Option<(String, Vec<(String, String)>)>,
+}
+
+/// Metadata is equal when its fields are equal,
+/// regardless of how it was formatted.
+impl PartialEq for ListMeta {
+    fn eq(&self, other: &Self) -> bool {
+        self.fields == other.fields
+    }
+}
+
+impl ListMeta {
+
+    /// Parse metadata from front matter text.
+    ///
+    /// Text that is not front matter has no fields.
+    ///
+    pub fn parse(text: &str) -> Self {
+        let front_matter_str = match item_parser::front_matter(text) {
+            Ok((_, x)) => x,
+            Err(_) => return ListMeta::default(),
+        };
+        let fields: Vec<(String, String)> = front_matter_str.split_inclusive('\n')
+            .filter_map(|x| item_parser::front_matter_field(x).ok())
+            .map(|(_, (key, value))| (key.into(), value.into()))
+            .collect();
+        ListMeta {
+            loaded: Some((front_matter_str.into(), fields.clone())),
+            fields,
+        }
+    }
+
+    /// Has this metadata changed since it was parsed?
+    ///
+    /// Metadata that was not parsed has changed if it has any fields.
+    ///
+    pub fn is_changed(&self) -> bool {
+        match &self.loaded {
+            Some((_, fields)) => *fields != self.fields,
+            None => !self.fields.is_empty(),
+        }
+    }
+
+    /// Get the value of the first field with a key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.iter().find(|(x, _)| x == key).map(|(_, value)| value.as_str())
+    }
+
+    /// Set the value of the field with a key, or remove the field.
+    ///
+    /// The first field with the key is changed in place, and any other
+    /// fields with the key are removed. A new field is added at the end.
+    ///
+    pub fn set(&mut self, key: &str, value: Option<&str>) {
+        let at = self.fields.iter().position(|(x, _)| x == key);
+        let mut i = 0;
+        self.fields.retain(|(x, _)| {
+            i += 1;
+            x != key || (Some(i - 1) == at && value.is_some())
+        });
+        match (at, value) {
+            (Some(at), Some(value)) => self.fields[at].1 = value.into(),
+            (None, Some(value)) => self.fields.push((key.into(), value.into())),
+            _ => {},
+        }
+    }
+
+    /// Get the title of the list.
+    pub fn title(&self) -> Option<&str> {
+        self.get(TITLE_KEY)
+    }
+
+    /// Get the owner of the list.
+    pub fn owner(&self) -> Option<&str> {
+        self.get(OWNER_KEY)
+    }
+
+    /// Get the labels that apply to all items of the list.
+    ///
+    /// The labels are separated by whitespace or commas,
+    /// and each is with or without its `#`.
+    ///
+    pub fn labels(&self) -> Vec<Label> {
+        self.get(LABELS_KEY).unwrap_or_default()
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter_map(Label::parse)
+            .collect()
+    }
+
+    /// Get the labels of an item, with the labels that apply to all
+    /// items of the list after its own labels, without duplicates.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// // meta labels are "#work #client:acme", item labels are [#client:acme, #urgent]
+    /// let labels = meta.labels_of(&item);
+    /// assert_eq!(labels, vec![Label::new(["client", "acme"]), Label::new(["urgent"]), Label::new(["work"])]);
+    /// ```
+    ///
+    pub fn labels_of(&self, item: &Item) -> Vec<Label> {
+        let mut labels: Vec<Label> = item.labels.clone().unwrap_or_default();
+        for label in self.labels() {
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+        }
+        labels
+    }
+
+    /// Get the checkbox marks that the list uses, or `None` if it
+    /// does not say, in which case every mark is in use.
+    ///
+    /// The marks are separated by whitespace or commas, and each is
+    /// with or without its checkbox, such as `[ ] [x] !`.
+    ///
+    pub fn marks(&self) -> Option<Vec<Status>> {
+        let value = self.get(MARKS_KEY)?;
+        let mut marks: Vec<Status> = Vec::new();
+        let mut rest = value;
+        loop {
+            rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+            if rest.is_empty() {
+                break;
+            }
+            let (mark, len) = match item_parser::checkbox(rest) {
+                Ok((after, (_, mark, _))) => (Status::from_mark(mark), rest.len() - after.len()),
+                Err(_) => {
+                    let len = rest.find(|c: char| c.is_whitespace() || c == ',').unwrap_or(rest.len());
+                    (Status::from_mark(&rest[..len]), len)
+                },
+            };
+            if !marks.contains(&mark) {
+                marks.push(mark);
+            }
+            rest = &rest[len..];
+        }
+        Some(marks)
+    }
+
+    /// Is a mark one of the marks that the list uses?
+    pub fn is_mark_in_use(&self, mark: Status) -> bool {
+        self.marks().is_none_or(|x| x.contains(&mark))
+    }
+
+    /// Get the date format of the list, in the strftime style.
+    pub fn date_format(&self) -> Option<&str> {
+        self.get(DATE_FORMAT_KEY)
+    }
+
+    /// Format a date with the date format of the list, or with
+    /// the default date format if the list has none, or if its
+    /// date format is not valid.
+    pub fn format_date(&self, date: NaiveDate) -> String {
+        let format = self.date_format().filter(|x| is_date_format(x)).unwrap_or(DATE_FORMAT_DEFAULT);
+        date.format(format).to_string()
+    }
+
+    /// Get diagnostics for fields with values that are not valid, and for
+    /// items with marks that the list does not use, with spans in the
+    /// source text that the metadata and items were parsed from.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let source = "---\nmarks: [ ] [x]\n---\n[?] foo\n";
+    /// let (items, _) = parse(source);
+    /// let diagnostics = ListMeta::parse(source).diagnostics(&items, source);
+    /// assert_eq!(diagnostics[0].to_string(), "4:2: warning: mark `?` is not in the marks of the list, which are `[ ] [x]`");
+    /// ```
+    ///
+    pub fn diagnostics(&self, items: &[Item], source: &str) -> Vec<Diagnostic> {
+        let mut vec = Vec::new();
+        if let Ok((_, front_matter_str)) = item_parser::front_matter(source) {
+            for (offset, line, _) in item_parser::code_lines(front_matter_str) {
+                let (key, value) = match item_parser::front_matter_field(line) {
+                    Ok((_, x)) => x,
+                    Err(_) => continue,
+                };
+                if key == DATE_FORMAT_KEY && !is_date_format(value) {
+                    let start = offset + line.find(value).unwrap_or(0);
+                    let message = "date format is not valid, such as `%Y-%m-%d` or `%d/%m/%Y`";
+                    vec.push(Diagnostic::new(Severity::Warning, message, source, start..start + value.len()));
+                }
+            }
+        }
+        if let Some(marks) = self.marks() {
+            let marks_str = marks.iter().map(|x| format!("[{}]", x)).collect::<Vec<_>>().join(" ");
+            for item in items {
+                let (mark, span, cst) = match (item.mark, &item.span, &item.cst) {
+                    (Some(mark), Some(span), Some(cst)) if cst.has_checkbox() && !marks.contains(&mark) => (mark, span, cst),
+                    _ => continue,
+                };
+                let start = span.bytes.start + cst.indent.len() + cst.list_marker.len() + cst.list_marker_spacer.len() + cst.checkbox_open.len();
+                let message = format!("mark `{}` is not in the marks of the list, which are `{}`", mark, marks_str);
+                vec.push(Diagnostic::new(Severity::Warning, message, source, start..start + cst.checkbox_mark.len()));
+            }
+        }
+        vec
+    }
+
+    /// Get the front matter text to write, which is the text as it was
+    /// parsed if the fields have not changed, or else the fields in the
+    /// default format, or nothing if there are no fields.
+    pub fn to_front_matter(&self) -> String {
+        match &self.loaded {
+            Some((text, _)) if !self.is_changed() => text.clone(),
+            _ if self.fields.is_empty() => String::new(),
+            _ => self.to_string(),
+        }
+    }
+
+}
+
+/// Write the fields as front matter in the default format.
+impl std::fmt::Display for ListMeta {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "---")?;
+        for (key, value) in self.fields.iter() {
+            writeln!(f, "{}: {}", key, value)?;
+        }
+        writeln!(f, "---")
+    }
+}
+
+/// Is text a valid date format in the strftime style?
+fn is_date_format(s: &str) -> bool {
+    !s.is_empty() && !StrftimeItems::new(s).any(|x| x == FormatItem::Error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::item_parser::parse;
+    use crate::load::load_items_via_str;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse() {
+        let meta = ListMeta::parse(indoc!{"
+            ---
+            title: Party planning
+            owner: Alice
+            labels: #personal, party #host:alice
+            marks: [ ] [x] [!] ?
+            date_format: %d/%m/%Y
+            color: blue
+            ---
+        "});
+        assert_eq!(meta.title(), Some("Party planning"));
+        assert_eq!(meta.owner(), Some("Alice"));
+        assert_eq!(meta.labels(), vec![Label::new(["personal"]), Label::new(["party"]), Label::new(["host", "alice"])]);
+        assert_eq!(meta.marks(), Some(vec![Status::Todo, Status::Done, Status::Urgent, Status::Custom('?')]));
+        assert_eq!(meta.date_format(), Some("%d/%m/%Y"));
+        assert_eq!(meta.get("color"), Some("blue"));
+        assert_eq!(meta.fields.len(), 6);
+        assert!(!meta.is_changed());
+        assert_eq!(ListMeta::parse("[ ] foo\n"), ListMeta::default());
+        assert_eq!(ListMeta::parse("---\ntitle: x\n").fields, vec![]);
+    }
+
+    #[test]
+    fn test_set() {
+        let mut meta = ListMeta::parse("---\ntitle: a\nowner: b\ntitle: c\n---\n");
+        meta.set("title", Some("d"));
+        assert_eq!(meta.fields, vec![("title".into(), "d".into()), ("owner".into(), "b".into())]);
+        assert!(meta.is_changed());
+        meta.set("owner", None);
+        meta.set("labels", Some("#work"));
+        assert_eq!(meta.to_front_matter(), "---\ntitle: d\nlabels: #work\n---\n");
+        meta.set("title", None);
+        meta.set("labels", None);
+        assert_eq!(meta.to_front_matter(), "");
+    }
+
+    #[test]
+    fn test_to_front_matter_unchanged() {
+        let text = "---  \ntitle:Party  \n---";
+        let meta = ListMeta::parse(text);
+        assert_eq!(meta.title(), Some("Party"));
+        assert_eq!(meta.to_front_matter(), text);
+        assert_eq!(ListMeta::default().to_front_matter(), "");
+    }
+
+    #[test]
+    fn test_labels_of() {
+        let meta = ListMeta::parse("---\nlabels: #work #client:acme\n---\n");
+        let items = load_items_via_str("[ ] foo #client:acme #urgent\n[ ] goo\n").unwrap();
+        assert_eq!(meta.labels_of(&items[0]), vec![Label::new(["client", "acme"]), Label::new(["urgent"]), Label::new(["work"])]);
+        assert_eq!(meta.labels_of(&items[1]), vec![Label::new(["work"]), Label::new(["client", "acme"])]);
+    }
+
+    #[test]
+    fn test_format_date() {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        assert_eq!(ListMeta::default().format_date(date), "2026-10-16");
+        assert_eq!(ListMeta::parse("---\ndate_format: %d/%m/%Y\n---\n").format_date(date), "16/10/2026");
+        assert_eq!(ListMeta::parse("---\ndate_format: %Q\n---\n").format_date(date), "2026-10-16");
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let source = indoc!{"
+            ---
+            marks: [ ] [x]
+            date_format: %Q
+            ---
+            - [x] foo
+            - [✓] goo
+            - [?] hoo
+            - note
+        "};
+        let (items, _) = parse(source);
+        let actual: Vec<String> = ListMeta::parse(source).diagnostics(&items, source).iter().map(|x| x.to_string()).collect();
+        assert_eq!(actual, vec![
+            "3:14: warning: date format is not valid, such as `%Y-%m-%d` or `%d/%m/%Y`",
+            "7:4: warning: mark `?` is not in the marks of the list, which are `[ ] [x]`",
+        ]);
+    }
+
+}
@@ -1,2 +1,20 @@
+pub mod clock;
+pub mod cst;
+pub mod depend;
+pub mod diagnostic;
+pub mod document;
+pub mod due;
+pub mod id;
+#[allow(clippy::module_inception)]
 pub mod item;
 pub mod item_parser;
+pub mod item_tree;
+pub mod label;
+pub mod meta;
+pub mod node;
+pub mod priority;
+pub mod recur;
+pub mod render;
+pub mod section;
+pub mod span;
+pub mod status;
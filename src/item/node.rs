@@ -0,0 +1,90 @@
+use crate::item::item::Item;
+use crate::item::item_parser;
+use crate::item::section::Heading;
+
+/// A node of a document of items, which is an item, or a part of the
+/// text between items, such as a heading or a comment.
+///
+/// Example:
+///
+/// ```
+/// let nodes = parse_nodes("Party plans\n\n<!-- draft -->\n[ ] foo\n");
+/// assert_eq!(nodes[0], Node::Prose("Party plans".into()));
+/// assert_eq!(nodes[1], Node::Comment("draft".into()));
+/// assert!(matches!(nodes[2], Node::Item(_)));
+/// ```
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    /// An item or a note.
+    Item(Box<Item>),
+    /// A heading, which starts a section of items.
+    Heading(Heading),
+    /// An include directive, with the path of the file to include.
+    Include(String),
+    /// A comment, such as `// foo` or `<!-- foo -->`, without its delimiters.
+    Comment(String),
+    /// A paragraph of free-form prose that is not in an item,
+    /// such as an introduction before the first item.
+    Prose(String),
+}
+
+/// Parse the nodes of source text, in order, as in `Document::nodes`,
+/// including the nodes of text that has no items.
+pub fn parse_nodes(source: &str) -> Vec<Node> {
+    item_parser::parse_document(source).0.nodes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::status::Status;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_nodes() {
+        let nodes = parse_nodes(indoc!{"
+            ---
+            title: Party
+            ---
+            Plans for the party,
+            which is in May.
+
+            <!-- Alice has
+                 the guest list -->
+            # Guests
+            - [ ] Call Alice
+            // ask about food
+            - [x] Send invitations
+
+            See you there!
+            #include:food.txt
+        "});
+        let item = |mark: Status, memo: &str| Node::Item(Box::new(Item {
+            nest: Some(0),
+            mark: Some(mark),
+            memo: Some(memo.into()),
+            ..Default::default()
+        }));
+        assert_eq!(nodes, vec![
+            Node::Prose("Plans for the party,\nwhich is in May.".into()),
+            Node::Comment("Alice has\n     the guest list".into()),
+            Node::Heading(Heading::new(1, "Guests")),
+            item(Status::Todo, "Call Alice"),
+            Node::Comment("ask about food".into()),
+            item(Status::Done, "Send invitations"),
+            Node::Prose("See you there!".into()),
+            Node::Include("food.txt".into()),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_nodes_without_items() {
+        assert_eq!(parse_nodes("Just prose\n\n// and a comment\n"), vec![
+            Node::Prose("Just prose".into()),
+            Node::Comment("and a comment".into()),
+        ]);
+        assert_eq!(parse_nodes(""), vec![]);
+    }
+
+}
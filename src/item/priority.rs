@@ -0,0 +1,189 @@
+use crate::item::item::Item;
+use crate::item::item_tree::ItemTree;
+
+/// Label name for a priority, such as `#priority:1` or `#priority:A`.
+pub static PRIORITY_LABEL_NAME: &str = "priority";
+
+/// A priority, either a number starting at 1, or a letter from `A` to `Z`
+/// as in todo.txt, where 1 and `A` are the most important.
+///
+/// Priorities are in order of importance, so the most important is the
+/// least. A number and a letter compare by rank, where `A` is 1, `B` is 2,
+/// and so on, and a number is before a letter of the same rank.
+///
+/// Example:
+///
+/// ```
+/// assert_eq!(Priority::parse("1"), Some(Priority::Number(1)));
+/// assert_eq!(Priority::parse("b"), Some(Priority::Letter('B')));
+/// assert!(Priority::Number(1) < Priority::Letter('B'));
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Priority {
+    Number(u32),
+    Letter(char),
+}
+
+impl Priority {
+
+    /// Parse a priority from a label value, such as `1` or `A`.
+    ///
+    /// Returns `None` if the text is not a number from 1,
+    /// nor a single letter from `A` to `Z` in either case.
+    ///
+    pub fn parse(s: &str) -> Option<Priority> {
+        let s = s.trim();
+        if let Ok(n) = s.parse::<u32>() {
+            return if n > 0 { Some(Priority::Number(n)) } else { None };
+        }
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii_alphabetic() => Some(Priority::Letter(c.to_ascii_uppercase())),
+            _ => None,
+        }
+    }
+
+    /// Get the rank, where 1 is the most important, and a letter
+    /// ranks by its place in the alphabet.
+    ///
+    /// A letter is ranked as its uppercase, and a char before `A`,
+    /// which `parse` does not make, is the least important.
+    ///
+    pub fn rank(&self) -> u32 {
+        match self {
+            Priority::Number(n) => *n,
+            Priority::Letter(c) => (c.to_ascii_uppercase() as u32)
+                .checked_sub('A' as u32)
+                .map_or(u32::MAX, |x| x + 1),
+        }
+    }
+
+}
+
+impl Ord for Priority {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.rank().cmp(&other.rank())
+            .then_with(|| matches!(self, Priority::Letter(_)).cmp(&matches!(other, Priority::Letter(_))))
+    }
+}
+
+impl PartialOrd for Priority {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Priority::Number(n) => write!(f, "{}", n),
+            Priority::Letter(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+/// Sort items by priority then by due date, within each group of
+/// siblings, so that each item keeps its parent and children.
+///
+/// The sort is stable, so items that are equal stay in order.
+/// Items without a priority have the default priority, if any,
+/// else are after items that have a priority.
+///
+/// Example:
+///
+/// ```
+/// let items = load_items_via_str("[ ] a #priority:2\n    [ ] a1\n[ ] b #priority:1\n").unwrap();
+/// let items = sort_items_by_priority(items, None);
+/// assert_eq!(save_items_via_string(&items), "[ ] b #priority:1\n[ ] a #priority:2\n    [ ] a1\n");
+/// ```
+///
+pub fn sort_items_by_priority(items: Vec<Item>, default: Option<Priority>) -> Vec<Item> {
+    let mut tree = ItemTree::from_items(items);
+    tree.sort_siblings_by(|a, b| a.cmp_by_priority(b, default).then_with(|| a.cmp_by_due(b)));
+    tree.into_items()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load::load_items_via_str;
+    use crate::save::save_items_via_string;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Priority::parse("1"), Some(Priority::Number(1)));
+        assert_eq!(Priority::parse("12"), Some(Priority::Number(12)));
+        assert_eq!(Priority::parse("A"), Some(Priority::Letter('A')));
+        assert_eq!(Priority::parse("z"), Some(Priority::Letter('Z')));
+        assert_eq!(Priority::parse("0"), None);
+        assert_eq!(Priority::parse("AB"), None);
+        assert_eq!(Priority::parse("é"), None);
+        assert_eq!(Priority::parse("high"), None);
+    }
+
+    #[test]
+    fn test_fmt() {
+        for s in ["1", "12", "A"] {
+            assert_eq!(Priority::parse(s).unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn test_ord() {
+        let mut priorities = [
+            Priority::Letter('B'),
+            Priority::Number(3),
+            Priority::Letter('A'),
+            Priority::Number(1),
+            Priority::Number(2),
+        ];
+        priorities.sort();
+        let actual: Vec<String> = priorities.iter().map(|x| x.to_string()).collect();
+        assert_eq!(actual, vec!["1", "A", "2", "B", "3"]);
+    }
+
+    #[test]
+    fn test_rank() {
+        assert_eq!(Priority::Number(3).rank(), 3);
+        assert_eq!(Priority::Letter('A').rank(), 1);
+        assert_eq!(Priority::Letter('b').rank(), 2);
+        assert_eq!(Priority::Letter('1').rank(), u32::MAX);
+    }
+
+    #[test]
+    fn test_sort_items_by_priority() {
+        let str = indoc!{"
+            [ ] a
+            [ ] b #priority:2
+                [ ] b1 #priority:B
+                    [ ] b1i
+                [ ] b2 #due:2026-10-21 #priority:A
+                [ ] b3 #due:2026-10-20 #priority:A
+            [ ] c #priority:1
+        "};
+        let items = load_items_via_str(str).unwrap();
+        let expect = indoc!{"
+            [ ] c #priority:1
+            [ ] b #priority:2
+                [ ] b3 #due:2026-10-20 #priority:A
+                [ ] b2 #due:2026-10-21 #priority:A
+                [ ] b1 #priority:B
+                    [ ] b1i
+            [ ] a
+        "};
+        assert_eq!(save_items_via_string(&sort_items_by_priority(items.clone(), None)), expect);
+        let expect = indoc!{"
+            [ ] a
+            [ ] c #priority:1
+            [ ] b #priority:2
+                [ ] b3 #due:2026-10-20 #priority:A
+                [ ] b2 #due:2026-10-21 #priority:A
+                [ ] b1 #priority:B
+                    [ ] b1i
+        "};
+        assert_eq!(save_items_via_string(&sort_items_by_priority(items, Priority::parse("1"))), expect);
+    }
+
+}
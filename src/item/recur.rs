@@ -0,0 +1,297 @@
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime};
+use crate::item::due::{Due, DEFER_LABEL_NAME};
+use crate::item::item::Item;
+use crate::item::status::Status;
+
+/// Label name for a recurrence, such as `#every:week`.
+pub static EVERY_LABEL_NAME: &str = "every";
+
+/// How often an item recurs.
+///
+/// A recurrence is written as a label, such as `#every:week`,
+/// `#every:2d`, or `#every:month:15` for the 15th of every month.
+///
+/// Example:
+///
+/// ```
+/// let recurrence = Recurrence::parse("month:15").unwrap();
+/// let date = NaiveDate::from_ymd_opt(2026, 10, 15).unwrap();
+/// assert_eq!(recurrence.next(date), NaiveDate::from_ymd_opt(2026, 11, 15));
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Recurrence {
+    Days(u32),
+    Weeks(u32),
+    /// Every n months, optionally on a day of the month.
+    Months(u32, Option<u32>),
+    Years(u32),
+}
+
+impl Recurrence {
+
+    /// Parse a recurrence from a label value, such as `day`, `week`,
+    /// `month`, `year`, `2d`, `3w`, `6m`, `1y`, or `month:15`.
+    pub fn parse(s: &str) -> Option<Recurrence> {
+        let s = s.trim().to_lowercase();
+        let (period, day) = match s.split_once(':') {
+            Some((period, day)) => (period, Some(day.parse::<u32>().ok().filter(|x| (1..=31).contains(x))?)),
+            None => (s.as_str(), None),
+        };
+        let (n, unit) = match period {
+            "day" | "daily" => (1, "d"),
+            "week" | "weekly" => (1, "w"),
+            "month" | "monthly" => (1, "m"),
+            "year" | "yearly" => (1, "y"),
+            _ => {
+                let (n, unit) = period.split_at(period.char_indices().last()?.0);
+                (n.parse::<u32>().ok().filter(|x| *x > 0)?, unit)
+            },
+        };
+        match (unit, day) {
+            ("d", None) => Some(Recurrence::Days(n)),
+            ("w", None) => Some(Recurrence::Weeks(n)),
+            ("m", day) => Some(Recurrence::Months(n, day)),
+            ("y", None) => Some(Recurrence::Years(n)),
+            _ => None,
+        }
+    }
+
+    /// Get the next date after a date.
+    ///
+    /// A day of the month that is not in a month, such as the 31st,
+    /// is the last day of that month.
+    ///
+    /// Returns `None` if the next date is out of the range of dates.
+    ///
+    pub fn next(&self, date: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Recurrence::Days(n) => date.checked_add_signed(Duration::try_days(*n as i64)?),
+            Recurrence::Weeks(n) => date.checked_add_signed(Duration::try_weeks(*n as i64)?),
+            Recurrence::Months(n, None) => date.checked_add_months(Months::new(*n)),
+            Recurrence::Months(n, Some(day)) => {
+                let month = date.with_day(1)?.checked_add_months(Months::new(*n))?;
+                (1..=*day).rev().find_map(|x| month.with_day(x))
+            },
+            Recurrence::Years(n) => date.checked_add_months(Months::new(n.checked_mul(12)?)),
+        }
+    }
+
+    /// Get the next date after a date that is also after today,
+    /// so that an item that is completed late does not recur in the past.
+    ///
+    /// Returns `None` if the next date is out of the range of dates.
+    ///
+    pub fn next_after(&self, date: NaiveDate, today: NaiveDate) -> Option<NaiveDate> {
+        let mut next = self.next(date)?;
+        while next <= today {
+            next = self.next(next)?;
+        }
+        Some(next)
+    }
+
+}
+
+impl std::fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Recurrence::Days(n) => write!(f, "{}d", n),
+            Recurrence::Weeks(n) => write!(f, "{}w", n),
+            Recurrence::Months(n, None) => write!(f, "{}m", n),
+            Recurrence::Months(n, Some(day)) => write!(f, "{}m:{}", n, day),
+            Recurrence::Years(n) => write!(f, "{}y", n),
+        }
+    }
+}
+
+/// Get the next occurrence of a recurring item, which is a fresh
+/// unchecked copy with the next due date.
+///
+/// The next due date is after the due date of the item, or after today
+/// if the item has no due date, and is always after today. A defer date
+/// moves by as many days as the due date, so it stays as far before it.
+///
+/// The copy keeps the tokens of the item, such as its list marker and
+/// its indentation, without the text around the item.
+///
+/// Returns `None` if the item does not recur, or if its next due date
+/// is out of the range of dates.
+///
+pub fn next_occurrence(item: &Item, now: NaiveDateTime) -> Option<Item> {
+    let recurrence = Recurrence::parse(&item.label_value(EVERY_LABEL_NAME)?)?;
+    let today = now.date();
+    let due = item.due_at(now);
+    let date = due.map_or(today, |x| x.date());
+    let next_date = recurrence.next_after(date, today)?;
+    let mut next = item.clone();
+    next.mark = Some(Status::Todo);
+    next.span = None;
+    if let Some(cst) = next.cst.as_mut() {
+        cst.lead.clear();
+        cst.tail.clear();
+    }
+    next.set_due(Some(match due {
+        Some(Due::DateTime(x)) => Due::DateTime(next_date.and_time(x.time())),
+        _ => Due::Date(next_date),
+    }));
+    if let Some(defer) = item.defer_at(now) {
+        let moved = match defer {
+            Due::Date(x) => x.checked_add_signed(next_date - date).map(Due::Date),
+            Due::DateTime(x) => x.checked_add_signed(next_date - date).map(Due::DateTime),
+        };
+        next.set_label_value(DEFER_LABEL_NAME, moved.map(|x| x.to_string()).as_deref());
+    }
+    Some(next)
+}
+
+/// Complete an item, by setting its mark to done, and if it recurs,
+/// insert its next occurrence after it, keeping the completed item for history.
+///
+/// The next occurrence is inserted directly after the item, so that
+/// the items nested under the item are nested under the next occurrence.
+///
+/// Returns the index of the next occurrence, if any.
+///
+/// Example:
+///
+/// ```
+/// let mut items = load_items_via_str("[ ] Water plants #every:week #due:2026-10-16\n").unwrap();
+/// complete(&mut items, 0, now);
+/// assert_eq!(save_items_via_string(&items), indoc!{"
+///     [x] Water plants #every:week #due:2026-10-16
+///     [ ] Water plants #every:week #due:2026-10-23
+/// "});
+/// ```
+///
+pub fn complete(items: &mut Vec<Item>, index: usize, now: NaiveDateTime) -> Option<usize> {
+    if items[index].mark == Some(Status::Done) {
+        return None;
+    }
+    items[index].mark = Some(Status::Done);
+    let next = next_occurrence(&items[index], now)?;
+    let at = index + 1;
+    items.insert(at, next);
+    Some(at)
+}
+
+/// Toggle an item between done and to do, as a checkbox does.
+///
+/// Completing a recurring item inserts its next occurrence, as in
+/// `complete`; unchecking it again does not remove the next occurrence.
+///
+pub fn toggle(items: &mut Vec<Item>, index: usize, now: NaiveDateTime) -> Option<usize> {
+    if items[index].mark == Some(Status::Done) {
+        items[index].mark = Some(Status::Todo);
+        return None;
+    }
+    complete(items, index, now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load::load_items_via_str;
+    use crate::save::save_items_via_string;
+    use indoc::indoc;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn now() -> NaiveDateTime {
+        date(2026, 10, 16).and_hms_opt(9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Recurrence::parse("day"), Some(Recurrence::Days(1)));
+        assert_eq!(Recurrence::parse("Week"), Some(Recurrence::Weeks(1)));
+        assert_eq!(Recurrence::parse("2d"), Some(Recurrence::Days(2)));
+        assert_eq!(Recurrence::parse("3w"), Some(Recurrence::Weeks(3)));
+        assert_eq!(Recurrence::parse("month:15"), Some(Recurrence::Months(1, Some(15))));
+        assert_eq!(Recurrence::parse("6m:31"), Some(Recurrence::Months(6, Some(31))));
+        assert_eq!(Recurrence::parse("yearly"), Some(Recurrence::Years(1)));
+        assert_eq!(Recurrence::parse("0d"), None);
+        assert_eq!(Recurrence::parse("week:15"), None);
+        assert_eq!(Recurrence::parse("month:32"), None);
+        assert_eq!(Recurrence::parse("often"), None);
+        assert_eq!(Recurrence::parse("2日"), None);
+    }
+
+    #[test]
+    fn test_fmt() {
+        for s in ["2d", "3w", "1m", "1m:15", "1y"] {
+            assert_eq!(Recurrence::parse(s).unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn test_next() {
+        assert_eq!(Recurrence::Days(2).next(date(2026, 10, 31)), Some(date(2026, 11, 2)));
+        assert_eq!(Recurrence::Weeks(1).next(date(2026, 10, 16)), Some(date(2026, 10, 23)));
+        assert_eq!(Recurrence::Months(1, None).next(date(2026, 1, 31)), Some(date(2026, 2, 28)));
+        assert_eq!(Recurrence::Months(1, Some(15)).next(date(2026, 10, 3)), Some(date(2026, 11, 15)));
+        assert_eq!(Recurrence::Months(1, Some(31)).next(date(2026, 1, 31)), Some(date(2026, 2, 28)));
+        assert_eq!(Recurrence::Years(1).next(date(2028, 2, 29)), Some(date(2029, 2, 28)));
+        assert_eq!(Recurrence::Days(99999999).next(date(2026, 10, 16)), None);
+        assert_eq!(Recurrence::Years(999999999).next(date(2026, 10, 16)), None);
+    }
+
+    #[test]
+    fn test_next_after() {
+        assert_eq!(Recurrence::Weeks(1).next_after(date(2026, 10, 1), date(2026, 10, 16)), Some(date(2026, 10, 22)));
+    }
+
+    #[test]
+    fn test_complete() {
+        let str = indoc!{"
+            - [ ] Water plants #every:week
+                  #due:2026-10-16T08:00
+              - [ ] Kitchen
+            - [ ] Call Alice
+        "};
+        let mut items = load_items_via_str(str).unwrap();
+        assert_eq!(complete(&mut items, 0, now()), Some(1));
+        assert_eq!(complete(&mut items, 0, now()), None);
+        assert_eq!(complete(&mut items, 3, now()), None);
+        let expect = indoc!{"
+            - [x] Water plants #every:week
+                  #due:2026-10-16T08:00
+            - [ ] Water plants #every:week
+                  #due:2026-10-23T08:00
+              - [ ] Kitchen
+            - [x] Call Alice
+        "};
+        assert_eq!(save_items_via_string(&items), expect);
+    }
+
+    #[test]
+    fn test_complete_with_defer() {
+        let mut items = load_items_via_str("\t* [ ] Review #every:2w #due:2026-10-16 #defer:2026-10-14\n").unwrap();
+        assert_eq!(complete(&mut items, 0, now()), Some(1));
+        assert_eq!(save_items_via_string(&items), indoc!{"
+            \t* [x] Review #every:2w #due:2026-10-16 #defer:2026-10-14
+            \t* [ ] Review #every:2w #due:2026-10-30 #defer:2026-10-28
+        "});
+    }
+
+    #[test]
+    fn test_complete_with_overflow() {
+        let mut items = load_items_via_str("[ ] foo #every:99999999d\n[ ] goo #every:999999999y\n").unwrap();
+        assert_eq!(complete(&mut items, 0, now()), None);
+        assert_eq!(toggle(&mut items, 1, now()), None);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].mark, Some(Status::Done));
+    }
+
+    #[test]
+    fn test_toggle() {
+        let mut items = load_items_via_str("[ ] Pay rent #every:month:1\n").unwrap();
+        assert_eq!(toggle(&mut items, 0, now()), Some(1));
+        assert_eq!(items[1].due(), Due::parse("2026-11-01"));
+        assert_eq!(toggle(&mut items, 0, now()), None);
+        assert_eq!(items[0].mark, Some(Status::Todo));
+        assert_eq!(items.len(), 2);
+    }
+
+}
@@ -0,0 +1,428 @@
+use crate::item::item::Item;
+use crate::item::item_parser;
+use crate::item::label::{find_labels, labels_from_memo, strip_labels, LabelDedup};
+use crate::item::meta::ListMeta;
+use crate::item::section::SectionPath;
+
+static CHECKBOX_OPEN_ASCII: &str = "["; // U+005B LEFT SQUARE BRACKET
+static CHECKBOX_SHUT_ASCII: &str = "]"; // U+005D RIGHT SQUARE BRACKET
+static CHECKBOX_OPEN_FULL_WIDTH: &str = "［"; // U+FF3B FULLWIDTH LEFT SQUARE BRACKET
+static CHECKBOX_SHUT_FULL_WIDTH: &str = "］"; // U+FF3D FULLWIDTH RIGHT SQUARE BRACKET
+static CHECKBOX_SUFFIX_DEFAULT: &str = " ";
+static LIST_MARKER_SPACER_DEFAULT: &str = " ";
+static NOTE_LIST_MARKER_DEFAULT: &str = "-";
+static MEMO_DEFAULT: &str = "?";
+
+/// Indent for each nest level of an item.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    /// 4 spaces for each nest level.
+    #[default]
+    Spaces,
+    /// 1 tab for each nest level.
+    Tabs,
+}
+
+impl Indent {
+
+    /// Get the text of the indent for one nest level.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Indent::Spaces => "    ",
+            Indent::Tabs => "\t",
+        }
+    }
+
+}
+
+/// Style of the brackets of a checkbox.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Brackets {
+    /// ASCII brackets, such as `[x]`.
+    #[default]
+    Ascii,
+    /// Full-width brackets, such as `［x］`.
+    FullWidth,
+}
+
+impl Brackets {
+
+    /// Get the open and the shut of a checkbox.
+    pub fn as_strs(&self) -> (&'static str, &'static str) {
+        match self {
+            Brackets::Ascii => (CHECKBOX_OPEN_ASCII, CHECKBOX_SHUT_ASCII),
+            Brackets::FullWidth => (CHECKBOX_OPEN_FULL_WIDTH, CHECKBOX_SHUT_FULL_WIDTH),
+        }
+    }
+
+}
+
+/// Placement of the labels of an item.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LabelPlacement {
+    /// Labels in the memo stay where they are, and any other labels,
+    /// such as stripped labels, are each on a line after the memo.
+    #[default]
+    Keep,
+    /// All labels are at the end of the first line of the memo.
+    Inline,
+    /// All labels are each on a line after the memo.
+    Trailing,
+}
+
+/// Options for rendering items as text.
+///
+/// The default options render items as `Display for Item` does,
+/// such as `[x] foo` with 4 spaces for each nest level.
+///
+/// Example:
+///
+/// ```
+/// let options = RenderOptions {
+///     list_marker: "-".into(),
+///     indent: Indent::Tabs,
+///     label_placement: LabelPlacement::Trailing,
+///     ..Default::default()
+/// };
+/// let items = load_items_via_str("[ ] foo #work\n    [x] goo\n").unwrap();
+/// assert_eq!(render_items(&items, &options), "- [ ] foo\n      #work\n\t- [x] goo\n");
+/// ```
+///
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// List marker before the checkbox, such as `-` or `*`, or empty for none.
+    ///
+    /// A note, which has no checkbox, always has a list marker,
+    /// which is `-` if this is empty.
+    ///
+    pub list_marker: String,
+    /// Indent for each nest level.
+    pub indent: Indent,
+    /// Style of the brackets of a checkbox.
+    pub brackets: Brackets,
+    /// Placement of the labels.
+    pub label_placement: LabelPlacement,
+    /// Width in chars to wrap memo lines at, including the indent,
+    /// or `None` to not wrap.
+    pub line_width: Option<usize>,
+}
+
+/// Render one item as text, without a line ending after it.
+///
+/// A note, which is an item without a mark, is rendered without
+/// a checkbox, such as `- foo`.
+///
+/// The continuation lines of the memo are indented to the memo column,
+/// so that paragraphs and indentation in the memo are kept.
+///
+/// Wrapping keeps labels whole, does not wrap lines in fenced code
+/// blocks or lines that are indented, and does not start a line with
+/// text that would open an item, a note, a heading, or a comment.
+///
+/// Example:
+///
+/// ```
+/// let options = RenderOptions { brackets: Brackets::FullWidth, line_width: Some(12), ..Default::default() };
+/// let item = Item { nest: Some(1), mark: Some(Status::Done), memo: Some("foo goo hoo".into()), ..Default::default() };
+/// assert_eq!(render_item(&item, &options), "    ［x］ foo\n        goo hoo");
+/// ```
+///
+pub fn render_item(item: &Item, options: &RenderOptions) -> String {
+    let nest = item.nest.unwrap_or(0) as usize;
+    let indent = options.indent.as_str().repeat(nest);
+    let (checkbox_open, checkbox_shut) = options.brackets.as_strs();
+    let list_marker = match item.mark {
+        None if options.list_marker.is_empty() => NOTE_LIST_MARKER_DEFAULT,
+        _ => options.list_marker.as_str(),
+    };
+    let mut prefix = String::new();
+    if !list_marker.is_empty() {
+        prefix.push_str(list_marker);
+        prefix.push_str(LIST_MARKER_SPACER_DEFAULT);
+    }
+    if let Some(mark) = item.mark {
+        prefix.push_str(checkbox_open);
+        prefix.push(mark.to_char());
+        prefix.push_str(checkbox_shut);
+        prefix.push_str(CHECKBOX_SUFFIX_DEFAULT);
+    }
+    let column = prefix.chars().count();
+    let continuation_indent = format!("{}{}", indent, " ".repeat(column));
+    let memo = memo_with_placed_labels(item, options.label_placement);
+    // A tab and 4 spaces are both 4 columns.
+    let width = options.line_width.map(|x| x.saturating_sub(nest * 4 + column).max(1));
+    let mut s = String::new();
+    for (i, line) in wrap_memo(&memo, width).iter().enumerate() {
+        if i == 0 {
+            s.push_str(&indent);
+            s.push_str(&prefix);
+            s.push_str(line);
+            continue;
+        }
+        s.push('\n');
+        if !line.is_empty() {
+            s.push_str(&continuation_indent);
+            s.push_str(line);
+        }
+    }
+    s
+}
+
+/// Render items as text, each with a line ending after it.
+///
+/// Each item is after headings for its section if the items before it
+/// are in another section, as new items are when they are saved.
+/// Items without a section are before the first heading, because
+/// a heading can not be closed.
+///
+pub fn render_items(items: &[Item], options: &RenderOptions) -> String {
+    let mut s = String::new();
+    let mut path = SectionPath::default();
+    let (items, items_in_sections): (Vec<&Item>, Vec<&Item>) = items.iter()
+        .partition(|x| x.section.as_ref().is_none_or(|x| x.is_empty()));
+    for item in items.into_iter().chain(items_in_sections) {
+        path.write_headings_to(item.section.as_deref().unwrap_or_default(), &mut s);
+        s.push_str(&render_item(item, options));
+        s.push('\n');
+    }
+    s
+}
+
+/// Render a list, which is its metadata as front matter, then its items.
+pub fn render_list(meta: &ListMeta, items: &[Item], options: &RenderOptions) -> String {
+    meta.to_front_matter() + &render_items(items, options)
+}
+
+/// Get the memo of an item with its labels placed, as the text to
+/// write after its checkbox.
+fn memo_with_placed_labels(item: &Item, placement: LabelPlacement) -> String {
+    if placement == LabelPlacement::Keep {
+        return item.memo_with_labels();
+    }
+    let memo = item.memo.as_deref().unwrap_or(MEMO_DEFAULT);
+    let mut labels = labels_from_memo(memo, LabelDedup::First).unwrap_or_default();
+    for label in item.labels.iter().flatten() {
+        if !labels.contains(label) {
+            labels.push(label.clone());
+        }
+    }
+    let labels: Vec<String> = labels.iter().map(|x| x.to_string()).collect();
+    let mut lines: Vec<String> = strip_labels(memo).split('\n').map(String::from).collect();
+    if labels.is_empty() {
+        return lines.join("\n");
+    }
+    match placement {
+        LabelPlacement::Inline => {
+            let first = lines[0].trim_end().to_string();
+            lines[0] = if first.is_empty() { labels.join(" ") } else { format!("{} {}", first, labels.join(" ")) };
+        },
+        _ => {
+            if lines.len() == 1 && lines[0].is_empty() {
+                lines.clear();
+            }
+            lines.extend(labels);
+        },
+    }
+    lines.join("\n")
+}
+
+/// Wrap the lines of a memo at a width, as in `render_item`.
+fn wrap_memo(memo: &str, width: Option<usize>) -> Vec<String> {
+    let mut vec = Vec::new();
+    for (_, line, is_code) in item_parser::code_lines(memo) {
+        let line = line.trim_end_matches(['\n', '\r']);
+        match width {
+            Some(width) if !is_code && !line.starts_with([' ', '\t']) && line.chars().count() > width => {
+                vec.extend(wrap_line(line, width));
+            },
+            _ => vec.push(line.to_string()),
+        }
+    }
+    if memo.ends_with('\n') || memo.is_empty() {
+        vec.push(String::new());
+    }
+    vec
+}
+
+/// Wrap one line at a width, at whitespace that is not in a label.
+fn wrap_line(line: &str, width: usize) -> Vec<String> {
+    let labels = find_labels(line);
+    let mut words: Vec<&str> = Vec::new();
+    let mut start = 0;
+    for (i, c) in line.char_indices() {
+        if c.is_whitespace() && !labels.iter().any(|(range, _)| range.contains(&i)) {
+            if start < i {
+                words.push(&line[start..i]);
+            }
+            start = i + c.len_utf8();
+        }
+    }
+    if start < line.len() {
+        words.push(&line[start..]);
+    }
+    let mut vec: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for (i, word) in words.iter().enumerate() {
+        // A line must not start with text that would be parsed as something else.
+        let rest = words[i..].join(" ");
+        let can_start_line = item_parser::continuation_line(&rest).is_ok();
+        if current.is_empty() || (current.chars().count() + 1 + word.chars().count() > width && can_start_line) {
+            if !current.is_empty() {
+                vec.push(std::mem::take(&mut current));
+            }
+            current.push_str(word);
+        } else {
+            current.push(' ');
+            current.push_str(word);
+        }
+    }
+    vec.push(current);
+    vec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::label::Label;
+    use crate::item::status::Status;
+    use crate::load::load_items_via_str;
+    use indoc::indoc;
+
+    #[test]
+    fn test_render_item_with_default_options() {
+        let items = load_items_via_str("- [x] foo #goo\n  more\n\n      paragraph\n").unwrap();
+        let mut item = items[0].clone();
+        item.labels.as_mut().unwrap().push(Label::new(["hoo"]));
+        assert_eq!(render_item(&item, &RenderOptions::default()), "[x] foo #goo\n    more\n\n    paragraph\n    #hoo");
+        assert_eq!(render_item(&Item::default(), &RenderOptions::default()), "[ ] ?");
+    }
+
+    #[test]
+    fn test_render_item_with_note() {
+        let items = load_items_via_str("- [ ] foo
+    * a note
+      more
+").unwrap();
+        assert_eq!(items[1].mark, None);
+        assert_eq!(render_item(&items[1], &RenderOptions::default()), "    - a note
+      more");
+        let options = RenderOptions { list_marker: "*".into(), ..Default::default() };
+        assert_eq!(render_items(&items, &options), "* [ ] foo
+    * a note
+      more
+");
+        assert_eq!(items[1].to_string(), "    - a note
+      more");
+    }
+
+    #[test]
+    fn test_render_item_with_list_marker_tabs_and_full_width_brackets() {
+        let options = RenderOptions {
+            list_marker: "*".into(),
+            indent: Indent::Tabs,
+            brackets: Brackets::FullWidth,
+            ..Default::default()
+        };
+        let item = Item { nest: Some(2), mark: Some(Status::Urgent), memo: Some("foo\ngoo".into()), ..Default::default() };
+        assert_eq!(render_item(&item, &options), "\t\t* ［!］ foo\n\t\t      goo");
+    }
+
+    #[test]
+    fn test_render_item_with_label_placement() {
+        let item = Item {
+            nest: Some(0),
+            mark: Some(Status::Todo),
+            memo: Some("call #alice about it\n#due:2026-10-20\nthen rest".into()),
+            labels: Some(vec![Label::new(["alice"]), Label::with_value("due", "2026-10-20"), Label::new(["home"])]),
+            ..Default::default()
+        };
+        let inline = RenderOptions { label_placement: LabelPlacement::Inline, ..Default::default() };
+        assert_eq!(render_item(&item, &inline), "[ ] call about it #alice #due:2026-10-20 #home\n    then rest");
+        let trailing = RenderOptions { label_placement: LabelPlacement::Trailing, ..Default::default() };
+        assert_eq!(render_item(&item, &trailing), "[ ] call about it\n    then rest\n    #alice\n    #due:2026-10-20\n    #home");
+        let labels_only = Item { memo: Some("#alice".into()), ..Default::default() };
+        assert_eq!(render_item(&labels_only, &inline), "[ ] #alice");
+        assert_eq!(render_item(&labels_only, &trailing), "[ ] #alice");
+    }
+
+    #[test]
+    fn test_render_item_with_line_width() {
+        let options = RenderOptions { list_marker: "-".into(), line_width: Some(20), ..Default::default() };
+        let item = Item {
+            nest: Some(0),
+            mark: Some(Status::Todo),
+            memo: Some("buy milk and eggs - and #\"bread rolls\" too\n  keep this indented line as it is\n```\na long line of code that is not wrapped\n```".into()),
+            ..Default::default()
+        };
+        assert_eq!(render_item(&item, &options), indoc!{r#"
+            - [ ] buy milk and
+                  eggs - and
+                  #"bread rolls"
+                  too
+                    keep this indented line as it is
+                  ```
+                  a long line of code that is not wrapped
+                  ```"#});
+    }
+
+    #[test]
+    fn test_render_items_round_trip() {
+        let options = RenderOptions {
+            list_marker: "-".into(),
+            indent: Indent::Tabs,
+            brackets: Brackets::FullWidth,
+            label_placement: LabelPlacement::Trailing,
+            line_width: Some(24),
+        };
+        let items = load_items_via_str(indoc!{"
+            # Work
+            [ ] foo #work and a memo that is long enough to wrap
+                [x] goo
+
+                    paragraph
+            # Home
+            [ ] hoo
+        "}).unwrap();
+        let rendered = render_items(&items, &options);
+        assert_eq!(rendered, indoc!{"
+            # Work
+
+            - ［ ］ foo and a memo
+                  that is long
+                  enough to wrap
+                  #work
+            \t- ［x］ goo
+
+            \t      paragraph
+
+            # Home
+
+            - ［ ］ hoo
+        "});
+        let reloaded = load_items_via_str(&rendered).unwrap();
+        let nests: Vec<_> = reloaded.iter().map(|x| (x.nest, x.mark, x.labels.clone(), x.section.clone())).collect();
+        let expect: Vec<_> = items.iter().map(|x| (x.nest, x.mark, x.labels.clone(), x.section.clone())).collect();
+        assert_eq!(nests, expect);
+        assert_eq!(reloaded[1].memo, Some("goo\n\nparagraph".into()));
+    }
+
+    #[test]
+    fn test_render_items_in_outer_section_and_without_section() {
+        let mut items = load_items_via_str("# Work\n## Website\n[ ] foo\n").unwrap();
+        items.push(Item { mark: Some(Status::Todo), memo: Some("goo".into()), section: Some(vec!["Work".into()]), ..Default::default() });
+        items.push(Item { mark: Some(Status::Todo), memo: Some("hoo".into()), ..Default::default() });
+        let rendered = render_items(&items, &RenderOptions::default());
+        assert_eq!(rendered, "[ ] hoo\n\n# Work\n\n## Website\n\n[ ] foo\n\n# Work\n\n[ ] goo\n");
+        let sections: Vec<_> = load_items_via_str(&rendered).unwrap().into_iter().map(|x| x.section).collect();
+        assert_eq!(sections, vec![None, Some(vec!["Work".into(), "Website".into()]), Some(vec!["Work".into()])]);
+    }
+
+    #[test]
+    fn test_render_list() {
+        let meta = ListMeta::parse("---\ntitle: Party\n---\n");
+        let items = load_items_via_str("[ ] foo\n").unwrap();
+        let options = RenderOptions { list_marker: "-".into(), ..Default::default() };
+        assert_eq!(render_list(&meta, &items, &options), "---\ntitle: Party\n---\n- [ ] foo\n");
+    }
+
+}
@@ -0,0 +1,227 @@
+use crate::item::item::Item;
+
+/// A Markdown heading, which starts a section of items,
+/// such as `## Website` or a line of text underlined by `===`.
+///
+/// Example:
+///
+/// ```
+/// let (_, heading) = heading("## Website\n").unwrap();
+/// assert_eq!(heading, Heading { level: 2, text: "Website".into() });
+/// assert_eq!(heading.to_string(), "## Website");
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    /// Level from 1 for `#` to 6 for `######`.
+    pub level: u8,
+    /// Text of the heading, without the `#` marks.
+    pub text: String,
+}
+
+impl Heading {
+
+    /// Create a heading.
+    pub fn new(level: u8, text: impl Into<String>) -> Self {
+        Heading { level, text: text.into() }
+    }
+
+}
+
+/// Write the heading in the ATX style, such as `## Website`.
+impl std::fmt::Display for Heading {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} {}", "#".repeat(self.level as usize), self.text)
+    }
+}
+
+/// The open headings of a section, from the outermost, as the
+/// headings of a file are read in order.
+///
+/// A heading closes any open heading of the same level or deeper,
+/// so that `# Work`, `## Website`, `## Mobile` is the path
+/// `Work` / `Mobile`.
+///
+/// Example:
+///
+/// ```
+/// let mut path = SectionPath::default();
+/// path.push(Heading::new(1, "Work"));
+/// path.push(Heading::new(2, "Website"));
+/// path.push(Heading::new(2, "Mobile"));
+/// assert_eq!(path.section(), Some(vec!["Work".into(), "Mobile".into()]));
+/// ```
+///
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SectionPath {
+    pub headings: Vec<Heading>,
+}
+
+impl SectionPath {
+
+    /// Open a heading, and close the headings it ends.
+    pub fn push(&mut self, heading: Heading) {
+        while self.headings.last().is_some_and(|x| x.level >= heading.level) {
+            self.headings.pop();
+        }
+        self.headings.push(heading);
+    }
+
+    /// Get the section as heading texts, as in `Item::section`,
+    /// or `None` if there are no open headings.
+    pub fn section(&self) -> Option<Vec<String>> {
+        if self.headings.is_empty() {
+            return None;
+        }
+        Some(self.headings.iter().map(|x| x.text.clone()).collect())
+    }
+
+    /// Get the headings to open a section from this path, which are
+    /// the headings for the section after the part that is the same.
+    ///
+    /// Each new heading is one level deeper than the heading before it.
+    /// If the section is the same as the start of this path, such as
+    /// `Work` after `# Work` and `## Website`, this is the last heading
+    /// of the section again, at its own level, which closes the headings
+    /// after it. An empty section has no headings, so an item without
+    /// a section is written before the first heading instead.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let mut path = SectionPath::default();
+    /// path.push(Heading::new(1, "Work"));
+    /// path.push(Heading::new(2, "Website"));
+    /// let headings = path.headings_to(&["Work".into(), "Mobile".into(), "iOS".into()]);
+    /// assert_eq!(headings, vec![Heading::new(2, "Mobile"), Heading::new(3, "iOS")]);
+    /// assert_eq!(path.headings_to(&["Work".into()]), vec![Heading::new(1, "Work")]);
+    /// ```
+    ///
+    pub fn headings_to(&self, section: &[String]) -> Vec<Heading> {
+        let same = self.headings.iter().zip(section)
+            .take_while(|(heading, text)| heading.text == **text)
+            .count();
+        if same > 0 && same == section.len() && same < self.headings.len() {
+            return vec![self.headings[same - 1].clone()];
+        }
+        let level = if same == 0 { 0 } else { self.headings[same - 1].level };
+        section[same..].iter().enumerate()
+            .map(|(i, text)| Heading::new((level + 1 + i as u8).min(6), text.clone()))
+            .collect()
+    }
+
+    /// Write the headings to open a section from this path, as in
+    /// `headings_to`, each with a blank line before it and after it,
+    /// and open them.
+    pub fn write_headings_to(&mut self, section: &[String], s: &mut String) {
+        for heading in self.headings_to(section) {
+            if !s.is_empty() && !s.ends_with("\n\n") {
+                s.push('\n');
+            }
+            s.push_str(&format!("{}\n\n", heading));
+            self.push(heading);
+        }
+    }
+
+}
+
+/// Get the items in a section or in its subsections,
+/// such as `["Work"]` for the items under `# Work` and `## Website`.
+///
+/// Example:
+///
+/// ```
+/// let items = load_items_via_str("# Work\n[ ] foo\n## Website\n[ ] goo\n# Home\n[ ] hoo\n").unwrap();
+/// let actual = items_in_section(&items, &["Work"]);
+/// assert_eq!(actual.len(), 2);
+/// ```
+///
+pub fn items_in_section<'a>(items: &'a [Item], section: &[&str]) -> Vec<&'a Item> {
+    items.iter().filter(|x| x.is_in_section(section)).collect()
+}
+
+/// Group items by their sections, in the order that each section
+/// first appears, with the item indexes of each section in order.
+///
+/// Items that are not in any section are in the group `None`.
+///
+/// Example:
+///
+/// ```
+/// let items = load_items_via_str("[ ] foo\n# Work\n[ ] goo\n[ ] hoo\n").unwrap();
+/// let groups = group_items_by_section(&items);
+/// assert_eq!(groups[1], (Some(vec!["Work".into()]), vec![1, 2]));
+/// ```
+///
+pub fn group_items_by_section(items: &[Item]) -> Vec<(Option<Vec<String>>, Vec<usize>)> {
+    let mut groups: Vec<(Option<Vec<String>>, Vec<usize>)> = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        match groups.iter_mut().find(|(section, _)| *section == item.section) {
+            Some((_, indexes)) => indexes.push(index),
+            None => groups.push((item.section.clone(), vec![index])),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load::load_items_via_str;
+    use indoc::indoc;
+
+    #[test]
+    fn test_section_path() {
+        let mut path = SectionPath::default();
+        assert_eq!(path.section(), None);
+        path.push(Heading::new(1, "Work"));
+        path.push(Heading::new(3, "Deep"));
+        path.push(Heading::new(2, "Website"));
+        assert_eq!(path.section(), Some(vec!["Work".into(), "Website".into()]));
+        path.push(Heading::new(1, "Home"));
+        assert_eq!(path.section(), Some(vec!["Home".into()]));
+    }
+
+    #[test]
+    fn test_headings_to() {
+        let mut path = SectionPath::default();
+        assert_eq!(path.headings_to(&["Work".into()]), vec![Heading::new(1, "Work")]);
+        path.push(Heading::new(1, "Work"));
+        path.push(Heading::new(3, "Website"));
+        assert_eq!(path.headings_to(&["Work".into(), "Website".into()]), vec![]);
+        assert_eq!(path.headings_to(&["Work".into(), "Mobile".into()]), vec![Heading::new(2, "Mobile")]);
+        assert_eq!(path.headings_to(&["Work".into()]), vec![Heading::new(1, "Work")]);
+        assert_eq!(path.headings_to(&["Home".into()]), vec![Heading::new(1, "Home")]);
+        assert_eq!(path.headings_to(&[]), vec![]);
+        path.push(Heading::new(1, "Work"));
+        assert_eq!(path.headings_to(&["Work".into()]), vec![]);
+    }
+
+    #[test]
+    fn test_items_in_section_and_groups() {
+        let items = load_items_via_str(indoc!{"
+            [ ] inbox
+
+            # Work
+            [ ] foo
+
+            ## Website
+            [ ] goo
+
+            # Home
+            [ ] hoo
+            [ ] joo
+        "}).unwrap();
+        let memos = |items: Vec<&Item>| items.into_iter().map(|x| x.memo.clone().unwrap()).collect::<Vec<_>>();
+        assert_eq!(memos(items_in_section(&items, &["Work"])), vec!["foo", "goo"]);
+        assert_eq!(memos(items_in_section(&items, &["Work", "Website"])), vec!["goo"]);
+        assert_eq!(memos(items_in_section(&items, &[])), vec!["inbox", "foo", "goo", "hoo", "joo"]);
+        assert_eq!(group_items_by_section(&items), vec![
+            (None, vec![0]),
+            (Some(vec!["Work".into()]), vec![1]),
+            (Some(vec!["Work".into(), "Website".into()]), vec![2]),
+            (Some(vec!["Home".into()]), vec![3, 4]),
+        ]);
+    }
+
+}
@@ -0,0 +1,59 @@
+use std::ops::{Range, RangeInclusive};
+
+/// Where an item is in the source text it was parsed from.
+///
+/// The span starts at the start of the checkbox line, and ends after
+/// the line ending of the last continuation line. It does not include
+/// blank lines before or after the item.
+///
+/// Example:
+///
+/// ```
+/// let items = load_items_via_str("[ ] foo\n\n[ ] goo\n    hoo\n").unwrap();
+/// let span = items[1].span.as_ref().unwrap();
+/// assert_eq!(span.lines, 3..=4);
+/// assert_eq!(span.bytes, 9..25);
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// Line numbers, starting at 1.
+    pub lines: RangeInclusive<usize>,
+    /// Byte range in the source text.
+    pub bytes: Range<usize>,
+}
+
+impl Span {
+
+    /// Get the text of the span in the source text.
+    pub fn text<'a>(&self, source: &'a str) -> &'a str {
+        &source[self.bytes.clone()]
+    }
+
+    /// Does the span include a byte offset?
+    pub fn contains_byte(&self, offset: usize) -> bool {
+        self.bytes.contains(&offset)
+    }
+
+    /// Does the span include a line number?
+    pub fn contains_line(&self, line: usize) -> bool {
+        self.lines.contains(&line)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span() {
+        let span = Span { lines: 2..=3, bytes: 4..12 };
+        assert_eq!(span.text("foo\n[ ] goo\nhoo"), "[ ] goo\n");
+        assert!(span.contains_byte(4));
+        assert!(!span.contains_byte(12));
+        assert!(span.contains_line(3));
+        assert!(!span.contains_line(1));
+    }
+
+}
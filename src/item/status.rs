@@ -0,0 +1,193 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Status of an item, as shown by its checkbox mark.
+///
+/// Example:
+///
+/// ```
+/// assert_eq!(Status::from_char('x'), Status::Done);
+/// assert_eq!(Status::from_char('✓'), Status::Done);
+/// assert_eq!(Status::from_char('?'), Status::Custom('?'));
+/// assert_eq!(Status::Done.to_char(), 'x');
+/// ```
+///
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Status {
+    /// To do, e.g. `[ ]`
+    #[default]
+    Todo,
+    /// Done, e.g. `[x]`
+    Done,
+    /// Urgent, e.g. `[!]`
+    Urgent,
+    /// Delegated, e.g. `[@]`
+    Delegated,
+    /// Deferred, e.g. `[.]`
+    Deferred,
+    /// Dropped, e.g. `[/]`
+    Dropped,
+    /// Any other mark, kept as-is so nothing is lost on round-trip.
+    Custom(char),
+}
+
+/// Mapping table of mark characters to status.
+///
+/// The first entry for each status is its canonical mark,
+/// which is the mark that `to_char` returns.
+///
+static MARKS: &[(char, Status)] = &[
+    (' ', Status::Todo),
+    ('\u{3000}', Status::Todo), // U+3000 IDEOGRAPHIC SPACE
+    ('☐', Status::Todo), // U+2610 BALLOT BOX
+    ('x', Status::Done),
+    ('X', Status::Done),
+    ('ｘ', Status::Done), // U+FF58 FULLWIDTH LATIN SMALL LETTER X
+    ('Ｘ', Status::Done), // U+FF38 FULLWIDTH LATIN CAPITAL LETTER X
+    ('✓', Status::Done), // U+2713 CHECK MARK
+    ('✔', Status::Done), // U+2714 HEAVY CHECK MARK
+    ('☑', Status::Done), // U+2611 BALLOT BOX WITH CHECK
+    ('!', Status::Urgent),
+    ('！', Status::Urgent), // U+FF01 FULLWIDTH EXCLAMATION MARK
+    ('@', Status::Delegated),
+    ('＠', Status::Delegated), // U+FF20 FULLWIDTH COMMERCIAL AT
+    ('.', Status::Deferred),
+    ('．', Status::Deferred), // U+FF0E FULLWIDTH FULL STOP
+    ('/', Status::Dropped),
+    ('／', Status::Dropped), // U+FF0F FULLWIDTH SOLIDUS
+    ('✗', Status::Dropped), // U+2717 BALLOT X
+    ('✘', Status::Dropped), // U+2718 HEAVY BALLOT X
+    ('☒', Status::Dropped), // U+2612 BALLOT BOX WITH X
+];
+
+impl Status {
+
+    /// Get the status for a mark character.
+    ///
+    /// Unknown marks become `Status::Custom`.
+    ///
+    pub fn from_char(c: char) -> Status {
+        MARKS
+            .iter()
+            .find(|(mark, _)| *mark == c)
+            .map_or(Status::Custom(c), |(_, status)| *status)
+    }
+
+    /// Get the status for a mark string, such as a captured checkbox mark.
+    ///
+    /// An empty string is treated as to do.
+    ///
+    pub fn from_mark(s: &str) -> Status {
+        s.chars().next().map_or(Status::Todo, Status::from_char)
+    }
+
+    /// Get the canonical mark character for this status.
+    pub fn to_char(self) -> char {
+        match self {
+            Status::Custom(c) => c,
+            status => MARKS
+                .iter()
+                .find(|(_, x)| *x == status)
+                .map(|(mark, _)| *mark)
+                .unwrap(),
+        }
+    }
+
+    /// Get a human-readable name for this status.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Todo => "todo",
+            Status::Done => "done",
+            Status::Urgent => "urgent",
+            Status::Delegated => "delegated",
+            Status::Deferred => "deferred",
+            Status::Dropped => "dropped",
+            Status::Custom(_) => "custom",
+        }
+    }
+
+}
+
+impl From<char> for Status {
+    fn from(c: char) -> Self {
+        Status::from_char(c)
+    }
+}
+
+impl From<&str> for Status {
+    fn from(s: &str) -> Self {
+        Status::from_mark(s)
+    }
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+impl Serialize for Status {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Status {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Status::from_mark(&s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_char() {
+        assert_eq!(Status::from_char(' '), Status::Todo);
+        assert_eq!(Status::from_char('x'), Status::Done);
+        assert_eq!(Status::from_char('!'), Status::Urgent);
+        assert_eq!(Status::from_char('@'), Status::Delegated);
+        assert_eq!(Status::from_char('.'), Status::Deferred);
+        assert_eq!(Status::from_char('/'), Status::Dropped);
+    }
+
+    #[test]
+    fn test_from_char_with_high_unicode() {
+        assert_eq!(Status::from_char('✓'), Status::Done); // U+2713 CHECK MARK
+        assert_eq!(Status::from_char('Ｘ'), Status::Done); // U+FF38 FULLWIDTH LATIN CAPITAL LETTER X
+        assert_eq!(Status::from_char('！'), Status::Urgent); // U+FF01 FULLWIDTH EXCLAMATION MARK
+        assert_eq!(Status::from_char('✗'), Status::Dropped); // U+2717 BALLOT X
+    }
+
+    #[test]
+    fn test_from_char_with_custom() {
+        assert_eq!(Status::from_char('?'), Status::Custom('?'));
+        assert_eq!(Status::from_char('?').to_char(), '?');
+    }
+
+    #[test]
+    fn test_from_mark() {
+        assert_eq!(Status::from_mark("x"), Status::Done);
+        assert_eq!(Status::from_mark(""), Status::Todo);
+    }
+
+    #[test]
+    fn test_to_char() {
+        assert_eq!(Status::Todo.to_char(), ' ');
+        assert_eq!(Status::Done.to_char(), 'x');
+        assert_eq!(Status::Urgent.to_char(), '!');
+        assert_eq!(Status::Delegated.to_char(), '@');
+        assert_eq!(Status::Deferred.to_char(), '.');
+        assert_eq!(Status::Dropped.to_char(), '/');
+        assert_eq!(Status::Custom('~').to_char(), '~');
+    }
+
+    #[test]
+    fn test_serde_json() {
+        assert_eq!(serde_json::to_string(&Status::Done).unwrap(), r#""x""#);
+        assert_eq!(serde_json::from_str::<Status>(r#""✓""#).unwrap(), Status::Done);
+        assert_eq!(serde_json::from_str::<Status>(r#""?""#).unwrap(), Status::Custom('?'));
+    }
+
+}
@@ -0,0 +1,9 @@
+//! To Do By: items of a to do list in Markdown-like text, and how to
+//! load them, save them, and work with them.
+
+pub mod item;
+pub mod load;
+pub mod save;
+
+#[cfg(test)]
+mod test_dir;
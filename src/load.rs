@@ -1,40 +1,30 @@
 use std::fs::File;
-use std::path::Path;
-use std::io::BufReader;
-use once_cell::sync::Lazy;
-use regex::Regex;
+use std::path::{Path, PathBuf};
+use crate::item::diagnostic::{Diagnostic, Severity};
+use crate::item::document::Document;
 use crate::item::item::Item;
+use crate::item::item_parser;
+use crate::item::label::{labels_from_memo, strip_labels, LabelDedup};
+use crate::item::meta::ListMeta;
 
-static REGEX_LINE_ITEM_OPEN: Lazy<Regex> = Lazy::new(||Regex::new(r"^\s*[\*\+\-•]?\s*\[.\]").unwrap());
-static REGEX_LINE_ITEM_OPEN_CAPTURES: Lazy<Regex> = Lazy::new(||Regex::new(r"^(\s*)[\*\+\-•]?\s*\[(.)\]\s*(.*)$").unwrap());
-static REGEX_LINE_BLANK: Lazy<Regex> = Lazy::new(||Regex::new(r"^\s*$").unwrap());
-
-#[derive(Debug, PartialEq)]
-enum State {
-    Do,
-    Doing,
+/// Options for loading items.
+#[derive(Debug, Default, Clone)]
+pub struct LoadOptions {
+    /// Policy for labels that appear more than once in an item.
+    pub label_dedup: LabelDedup,
+    /// Strip labels from the memo; they are still kept in the label fields.
+    pub strip_labels: bool,
 }
 
-#[derive(Debug, PartialEq)]
-enum LineKind {
-    Blank,
-    ItemOpen,
-    Other,
-}
-
-/// Calculate the nest level.
-/// 
-/// Nesting can be done by any combination of:
-/// 
-///    * 1 tab
-///    * 4 spaces
-/// 
-pub fn whitespace_to_nest(s: &str) -> u8 {
-    s.matches("\t").count() as u8 +
-    (s.matches(" ").count() as u8 / 4)
-}
+/// Documents of files, each with the path of its file,
+/// in the order that the files are loaded.
+pub type Documents = Vec<(PathBuf, Document)>;
 
 /// Load items via path.
+///
+/// This includes the items of any files that are included by include
+/// directives, such as `#include:areas/work.txt`, in place of each
+/// directive, and each item has the path of the file it came from.
 /// 
 /// ```
 /// let path = Path::new("example.txt");
@@ -42,7 +32,29 @@ pub fn whitespace_to_nest(s: &str) -> u8 {
 /// ```
 /// 
 pub fn load_items_via_path(path: &Path) -> ::std::io::Result<Vec<Item>> {
-    load_items_via_file(::std::fs::File::open(path)?)
+    let (items, diagnostics) = load_items_with_diagnostics_via_path(path)?;
+    check_diagnostics(diagnostics)?;
+    Ok(items)
+}
+
+/// Load items with diagnostics via path, with the items of any files
+/// that are included, as in `load_items_via_path`.
+///
+/// The diagnostics include errors for included files that can not be
+/// read, such as missing files, and for include cycles, such as a file
+/// that includes itself. A diagnostic for an included file has its path.
+///
+/// ```
+/// let path = Path::new("example.txt");
+/// let (items, diagnostics) = load_items_with_diagnostics_via_path(path).unwrap();
+/// for diagnostic in diagnostics {
+///     eprintln!("{}", diagnostic);
+/// }
+/// ```
+///
+pub fn load_items_with_diagnostics_via_path(path: &Path) -> ::std::io::Result<(Vec<Item>, Vec<Diagnostic>)> {
+    let (_, items, diagnostics) = load_list_with_includes(path, &mut Vec::new())?;
+    Ok((items, diagnostics))
 }
 
 /// Load items via file.
@@ -86,87 +98,407 @@ pub fn load_items_via_read(read: impl ::std::io::Read) -> ::std::io::Result<Vec<
 /// ```
 /// 
 pub fn load_items_via_buf_read(buf_read: impl std::io::BufRead) -> ::std::io::Result<Vec<Item>> {
+    load_items_via_buf_read_with_options(buf_read, &LoadOptions::default())
+}
+
+/// Load items via buf read with options.
+/// 
+/// ```
+/// let buf_read = BufReader::new(File::open("example.txt").unwrap())
+/// let options = LoadOptions { strip_labels: true, ..Default::default() };
+/// let items = load_items_via_buf_read_with_options(str, &options).unwrap();
+/// ```
+/// 
+pub fn load_items_via_buf_read_with_options(buf_read: impl std::io::BufRead, options: &LoadOptions) -> ::std::io::Result<Vec<Item>> {
+    let (items, diagnostics) = load_items_with_diagnostics_via_buf_read_with_options(buf_read, options)?;
+    check_diagnostics(diagnostics)?;
+    Ok(items)
+}
+
+/// Load a list, which is its metadata and its items, via path,
+/// with the items of any files that are included, as in `load_items_via_path`.
+///
+/// ```
+/// let path = Path::new("example.txt");
+/// let (meta, items) = load_list_via_path(path).unwrap();
+/// ```
+///
+pub fn load_list_via_path(path: &Path) -> ::std::io::Result<(ListMeta, Vec<Item>)> {
+    let (mut documents, items, diagnostics) = load_list_with_includes(path, &mut Vec::new())?;
+    check_diagnostics(diagnostics)?;
+    Ok((documents.swap_remove(0).1.meta, items))
+}
+
+/// Load a list, which is its metadata and its items, via str.
+///
+/// The metadata is from the front matter at the start of the text,
+/// which is `key: value` lines between `---` lines.
+///
+/// ```
+/// let str = "---\ntitle: Party\n---\n[ ] foo\n";
+/// let (meta, items) = load_list_via_str(str).unwrap();
+/// assert_eq!(meta.title(), Some("Party"));
+/// ```
+///
+pub fn load_list_via_str(str: &str) -> ::std::io::Result<(ListMeta, Vec<Item>)> {
+    load_list_via_buf_read_with_options(str.as_bytes(), &LoadOptions::default())
+}
+
+/// Load a list, which is its metadata and its items, via buf read with options.
+///
+/// ```
+/// let buf_read = BufReader::new(File::open("example.txt").unwrap())
+/// let options = LoadOptions { strip_labels: true, ..Default::default() };
+/// let (meta, items) = load_list_via_buf_read_with_options(buf_read, &options).unwrap();
+/// ```
+///
+pub fn load_list_via_buf_read_with_options(mut buf_read: impl std::io::BufRead, options: &LoadOptions) -> ::std::io::Result<(ListMeta, Vec<Item>)> {
+    let mut str = String::new();
+    buf_read.read_to_string(&mut str)?;
+    let (meta, mut items, diagnostics) = item_parser::parse_list(&str);
+    check_diagnostics(diagnostics)?;
+    for item in items.iter_mut() {
+        apply_options(item, options);
+    }
+    Ok((meta, items))
+}
+
+/// Load a document, which is its metadata, and its items with the text
+/// between them, via str.
+///
+/// ```
+/// let document = load_document_via_str("Intro\n\n[ ] foo\n").unwrap();
+/// assert_eq!(document.parts[0], Part::Text("Intro\n\n".into()));
+/// ```
+///
+pub fn load_document_via_str(str: &str) -> ::std::io::Result<Document> {
+    let (mut document, diagnostics) = item_parser::parse_document(str);
+    check_diagnostics(diagnostics)?;
+    for item in document.items_mut() {
+        apply_options(item, &LoadOptions::default());
+    }
+    Ok(document)
+}
+
+/// Load a document via path, which is only the file of the path,
+/// with its include directives as text.
+///
+/// ```
+/// let path = Path::new("example.txt");
+/// let document = load_document_via_path(path).unwrap();
+/// ```
+///
+pub fn load_document_via_path(path: &Path) -> ::std::io::Result<Document> {
+    let mut document = load_document_via_str(&::std::fs::read_to_string(path)?)?;
+    for item in document.items_mut() {
+        item.file = Some(path.to_path_buf());
+    }
+    Ok(document)
+}
+
+/// Load the documents of a path and of the files that it includes, each
+/// with the path of its file, in the order that the files are loaded,
+/// with diagnostics, as in `load_items_with_diagnostics_via_path`.
+///
+/// ```
+/// let path = Path::new("example.txt");
+/// let (documents, diagnostics) = load_documents_with_diagnostics_via_path(path).unwrap();
+/// ```
+///
+pub fn load_documents_with_diagnostics_via_path(path: &Path) -> ::std::io::Result<(Documents, Vec<Diagnostic>)> {
+    let (documents, _, diagnostics) = load_list_with_includes(path, &mut Vec::new())?;
+    Ok((documents, diagnostics))
+}
+
+/// Load items with diagnostics via str.
+///
+/// The diagnostics are for text that can not be parsed, and for text
+/// that is likely a typo, such as a checkbox `[xx]`.
+///
+/// ```
+/// let str = "[ ] foo\n[xx] goo\n";
+/// let (items, diagnostics) = load_items_with_diagnostics_via_str(str).unwrap();
+/// for diagnostic in diagnostics {
+///     eprint!("{}", diagnostic.render("example.txt", str));
+/// }
+/// ```
+///
+pub fn load_items_with_diagnostics_via_str(str: &str) -> ::std::io::Result<(Vec<Item>, Vec<Diagnostic>)> {
+    load_items_with_diagnostics_via_buf_read_with_options(str.as_bytes(), &LoadOptions::default())
+}
+
+/// Load items with diagnostics via buf read with options.
+///
+/// ```
+/// let buf_read = BufReader::new(File::open("example.txt").unwrap())
+/// let options = LoadOptions { strip_labels: true, ..Default::default() };
+/// let (items, diagnostics) = load_items_with_diagnostics_via_buf_read_with_options(buf_read, &options).unwrap();
+/// ```
+///
+pub fn load_items_with_diagnostics_via_buf_read_with_options(mut buf_read: impl std::io::BufRead, options: &LoadOptions) -> ::std::io::Result<(Vec<Item>, Vec<Diagnostic>)> {
+    let mut str = String::new();
+    buf_read.read_to_string(&mut str)?;
+    let (mut items, diagnostics) = item_parser::parse(&str);
+    for item in items.iter_mut() {
+        apply_options(item, options);
+    }
+    Ok((items, diagnostics))
+}
+
+/// Load a list via path, with the items of the files that it includes
+/// in place of each include directive, and with diagnostics, and with
+/// the document of each file, in the order that the files are loaded.
+///
+/// The paths of the files that are being loaded are on the stack, to
+/// find include cycles. The diagnostics of the file at the bottom of
+/// the stack have no path, and the diagnostics of included files do.
+fn load_list_with_includes(path: &Path, stack: &mut Vec<PathBuf>) -> ::std::io::Result<(Documents, Vec<Item>, Vec<Diagnostic>)> {
+    let source = ::std::fs::read_to_string(path)?;
+    let (mut document, mut diagnostics) = item_parser::parse_document(&source);
+    for item in document.items_mut() {
+        apply_options(item, &LoadOptions::default());
+        item.file = Some(path.to_path_buf());
+    }
+    stack.push(path.to_path_buf());
+    let mut documents: Documents = Vec::new();
     let mut vec: Vec<Item> = Vec::new();
-    let mut state = State::Do;
-    let mut line_kind: LineKind;
-    let mut nest = 0 as u8;
-    let mut mark = String::from("?");
-    let mut memo = String::from("?");
-    let lines = buf_read.lines();
-    for line in lines {
-        let s = line?;
-        println!("line: {}", s);
- 
-        // What line kind are we handling?
-        line_kind = if REGEX_LINE_ITEM_OPEN.is_match(&s) {
-            LineKind::ItemOpen
-        } else if REGEX_LINE_BLANK.is_match(&s) {
-            LineKind::Blank
-        } else {
-            LineKind::Other  
-        };
+    let mut items = document.clone().into_items().into_iter().peekable();
+    for (range, include_str) in item_parser::find_includes(&source) {
+        while let Some(item) = items.next_if(|x| x.span.as_ref().is_some_and(|x| x.bytes.start < range.start)) {
+            vec.push(item);
+        }
+        let include_path = path.parent().unwrap_or(Path::new("")).join(include_str);
+        let diagnostic = |message: String| Diagnostic::new(Severity::Error, message, &source, range.clone());
+        if let Some(at) = stack.iter().position(|x| canonical_path(x) == canonical_path(&include_path)) {
+            let cycle: Vec<String> = stack[at..].iter().map(|x| format!("`{}`", x.display())).chain([format!("`{}`", include_path.display())]).collect();
+            diagnostics.push(diagnostic(format!("include cycle: {}", cycle.join(" -> "))));
+            continue;
+        }
+        match load_list_with_includes(&include_path, stack) {
+            Ok((included_documents, included_items, included_diagnostics)) => {
+                documents.extend(included_documents);
+                vec.extend(included_items);
+                diagnostics.extend(included_diagnostics);
+            },
+            Err(e) if e.kind() == ::std::io::ErrorKind::NotFound => {
+                diagnostics.push(diagnostic(format!("included file is not found: `{}`", include_path.display())));
+            },
+            Err(e) => {
+                diagnostics.push(diagnostic(format!("included file can not be read: `{}`: {}", include_path.display(), e)));
+            },
+        }
+    }
+    vec.extend(items);
+    stack.pop();
+    // The diagnostics of included files already have their paths.
+    if !stack.is_empty() {
+        for diagnostic in diagnostics.iter_mut().filter(|x| x.file.is_none()) {
+            diagnostic.file = Some(path.to_path_buf());
+        }
+    }
+    documents.insert(0, (path.to_path_buf(), document));
+    Ok((documents, vec, diagnostics))
+}
 
-        // If there's an item in progress, then can we finish it?
-        if state == State::Doing && (line_kind == LineKind::ItemOpen || line_kind == LineKind::Blank) {
-            vec.push(Item {
-                nest: Some(nest.clone()),
-                mark: Some(mark.clone()),
-                memo: Some(memo.clone()),
-                label1s: None,
-                label2s: None,
-            })
+/// Get the canonical path of a file, to compare paths to the same file,
+/// or else the path as-is, such as for a file that does not exist.
+fn canonical_path(path: &Path) -> PathBuf {
+    ::std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Error for loading items.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The input can not be read.
+    Io(::std::io::Error),
+    /// The input can not be parsed.
+    Parse(Diagnostic),
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "{}", e),
+            LoadError::Parse(diagnostic) => write!(f, "{}", diagnostic),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Io(e) => Some(e),
+            LoadError::Parse(_) => None,
         }
-        match line_kind {
-            LineKind::ItemOpen => {
-                state = State::Doing;
-                if let Some(captures) = REGEX_LINE_ITEM_OPEN_CAPTURES.captures(&s) {
-                    nest = captures.get(1).map_or(0, |m| whitespace_to_nest(m.as_str()));
-                    mark = String::from(captures.get(2).map_or("?", |m| m.as_str()));
-                    memo = String::from(captures.get(3).map_or("?", |m| m.as_str().trim()));
+    }
+}
+
+impl From<::std::io::Error> for LoadError {
+    fn from(e: ::std::io::Error) -> Self {
+        LoadError::Io(e)
+    }
+}
+
+/// Iterator that loads items one at a time via buf read.
+///
+/// This keeps only the text of the current item in memory, so it can
+/// read files of any size, such as archives of many years of items.
+/// Each item is yielded when the next item starts, or at the end.
+///
+/// ```
+/// let buf_read = BufReader::new(File::open("archive.txt").unwrap());
+/// let done = ItemReader::new(buf_read)
+///     .filter_map(Result::ok)
+///     .filter(|item| item.mark == Some(Status::Done))
+///     .count();
+/// ```
+///
+pub struct ItemReader<R: std::io::BufRead> {
+    buf_read: R,
+    options: LoadOptions,
+    context: item_parser::Context,
+    boundary: item_parser::ItemBoundary,
+    /// Text that is read and not yet in an item, which is the text of
+    /// the current item, including blank lines and headings before it,
+    /// and any lines after it that are read to know where it ends.
+    text: String,
+    /// Line buffer, which is reused for each line.
+    line: String,
+    /// Does the last line that is read open an item, which ends any item before it?
+    is_ending: bool,
+    done: bool,
+}
+
+impl<R: std::io::BufRead> ItemReader<R> {
+
+    /// Create an item reader with the default load options.
+    pub fn new(buf_read: R) -> Self {
+        Self::with_options(buf_read, LoadOptions::default())
+    }
+
+    /// Create an item reader with load options.
+    pub fn with_options(buf_read: R, options: LoadOptions) -> Self {
+        ItemReader {
+            buf_read,
+            options,
+            context: item_parser::Context::default(),
+            boundary: item_parser::ItemBoundary::default(),
+            text: String::new(),
+            line: String::new(),
+            is_ending: false,
+            done: false,
+        }
+    }
+
+    /// Parse the text of the next item, with the text after it at the end.
+    fn parse(&mut self, text: &str, tail: &str) -> Result<Item, LoadError> {
+        match self.context.parse_item(text) {
+            Ok(mut item) => {
+                apply_options(&mut item, &self.options);
+                if let Some(cst) = item.cst.as_mut() {
+                    cst.tail = tail.into();
                 }
+                Ok(item)
             },
-            LineKind::Blank => {
-                state = State::Do;
-            },
-            LineKind::Other => {
-                state = State::Doing;
-                memo.push_str("\n");
-                memo.push_str(&s.trim());
+            Err(diagnostic) => Err(LoadError::Parse(diagnostic)),
+        }
+    }
+
+    /// Get the length of the first item of the text, with the text
+    /// before it, if it ends before the last line that is read.
+    fn item_len(&self) -> Option<usize> {
+        let start = self.context.front_matter_len(&self.text);
+        let (rest, _) = item_parser::item_cst(&self.text[start..]).ok()?;
+        let len = self.text.len() - rest.len();
+        (len <= self.text.len() - self.line.len()).then_some(len)
+    }
+
+    /// Parse the last item, with the blank lines and headings after it,
+    /// or `None` if there are only blank lines and headings.
+    fn finish(&mut self) -> Option<Result<Item, LoadError>> {
+        let text = std::mem::take(&mut self.text);
+        let start = self.context.front_matter_len(&text);
+        if matches!(item_parser::lead(&text[start..]), Ok((rest, _)) if rest.trim().is_empty()) {
+            return None;
+        }
+        let len = match item_parser::item_cst(&text[start..]) {
+            Ok((rest, _)) if item_parser::lead(rest).is_ok_and(|(x, _)| x.trim().is_empty()) => text.len() - rest.len(),
+            _ => text.len(),
+        };
+        Some(self.parse(&text[..len], &text[len..]))
+    }
+
+}
+
+impl<R: std::io::BufRead> Iterator for ItemReader<R> {
+    type Item = Result<Item, LoadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.is_ending {
+                match self.item_len() {
+                    Some(len) => {
+                        let rest = self.text.split_off(len);
+                        let text = std::mem::replace(&mut self.text, rest);
+                        return Some(self.parse(&text, ""));
+                    },
+                    None => self.is_ending = false,
+                }
+            }
+            if self.done {
+                if let Some(len) = self.context.item_len(&self.text, true) {
+                    let rest = self.text.split_off(len);
+                    let text = std::mem::replace(&mut self.text, rest);
+                    return Some(self.parse(&text, ""));
+                }
+                return self.finish();
+            }
+            self.line.clear();
+            match self.buf_read.read_line(&mut self.line) {
+                Ok(0) => self.done = true,
+                Ok(_) => {
+                    self.is_ending = self.boundary.line(&self.line);
+                    self.text.push_str(&self.line);
+                },
+                Err(e) => {
+                    self.done = true;
+                    self.text.clear();
+                    return Some(Err(e.into()));
+                },
             }
         }
     }
-    if state == State::Doing {
-        vec.push(Item {
-            nest: Some(nest.clone()),
-            mark: Some(mark.clone()),
-            memo: Some(memo.clone()),
-            label1s: None,
-            label2s: None,
-        })
+}
+
+/// Check diagnostics for a loader that returns no diagnostics, which fails
+/// with the first error diagnostic, if any, as invalid data.
+fn check_diagnostics(diagnostics: Vec<Diagnostic>) -> ::std::io::Result<()> {
+    match diagnostics.into_iter().find(|x| x.severity == Severity::Error) {
+        Some(diagnostic) => Err(::std::io::Error::new(::std::io::ErrorKind::InvalidData, diagnostic.to_string())),
+        None => Ok(()),
+    }
+}
+
+/// Apply the load options to a parsed item, as if it were loaded that way.
+fn apply_options(item: &mut Item, options: &LoadOptions) {
+    let memo = item.memo.take().unwrap_or_default();
+    item.labels = labels_from_memo(&memo, options.label_dedup);
+    item.memo = Some(if options.strip_labels { strip_labels(&memo) } else { memo });
+    let loaded = Box::new(Item { cst: None, span: None, ..item.clone() });
+    if let Some(cst) = item.cst.as_mut() {
+        cst.loaded = loaded;
     }
-    Ok(vec)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::item::cst::LineCst;
+    use crate::item::label::Label;
+    use crate::item::span::Span;
+    use crate::test_dir::TestDir;
     use indoc::indoc;
 
-    #[test]
-    fn test_whitespace_to_nest() {
-        assert_eq!(whitespace_to_nest(""), 0);
-        assert_eq!(whitespace_to_nest(" "), 0);
-        assert_eq!(whitespace_to_nest("  "), 0);
-        assert_eq!(whitespace_to_nest("   "), 0);
-        assert_eq!(whitespace_to_nest("    "), 1);
-        assert_eq!(whitespace_to_nest("     "), 1);
-        assert_eq!(whitespace_to_nest("      "), 1);
-        assert_eq!(whitespace_to_nest("       "), 1);
-        assert_eq!(whitespace_to_nest("        "), 2);
-        assert_eq!(whitespace_to_nest("\t"), 1);
-        assert_eq!(whitespace_to_nest("\t\t"), 2);
-    }
-
     #[test]
     fn test_load_items_via_str() {
         let str = indoc!{"
@@ -180,22 +512,22 @@ mod tests {
                 nest: Some(0),
                 mark: Some(" ".into()),
                 memo: Some("foo".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
             Item {
                 nest: Some(0),
                 mark: Some("!".into()),
                 memo: Some("goo".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
             Item {
                 nest: Some(0),
                 mark: Some("x".into()),
                 memo: Some("hoo".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
         ];
         assert_eq!(actual, expect);
@@ -215,22 +547,22 @@ mod tests {
                 nest: Some(0),
                 mark: Some(" ".into()),
                 memo: Some("foo".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
             Item {
                 nest: Some(0),
                 mark: Some("!".into()),
                 memo: Some("goo".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
             Item {
                 nest: Some(0),
                 mark: Some("x".into()),
                 memo: Some("hoo".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
         ];
         assert_eq!(actual, expect);
@@ -245,22 +577,22 @@ mod tests {
                     nest: Some(0),
                     mark: Some(" ".into()),
                     memo: Some("alpha1".into()),
-                    label1s: None,
-                    label2s: None,
+                    labels: None,
+                    ..Default::default()
                 },
                 Item {
                     nest: Some(0),
                     mark: Some("!".into()),
                     memo: Some("bravo1".into()),
-                    label1s: None,
-                    label2s: None,
+                    labels: None,
+                    ..Default::default()
                 },
                 Item {
                     nest: Some(0),
                     mark: Some("x".into()),
                     memo: Some("charlie1".into()),
-                    label1s: None,
-                    label2s: None,
+                    labels: None,
+                    ..Default::default()
                 },
             ]
         }
@@ -272,7 +604,7 @@ mod tests {
                 [!] bravo1
                 [x] charlie1
             "};
-            let actual = load_items_via_str(&str).unwrap();
+            let actual = load_items_via_str(str).unwrap();
             assert_eq!(actual, expect());
         }
 
@@ -287,7 +619,7 @@ mod tests {
                 [x] charlie1
 
             "};
-            let actual = load_items_via_str(&str).unwrap();
+            let actual = load_items_via_str(str).unwrap();
             assert_eq!(actual, expect());
         }
 
@@ -302,22 +634,22 @@ mod tests {
                     nest: Some(0),
                     mark: Some(" ".into()),
                     memo: Some("alpha1\nalpha2".into()),
-                    label1s: None,
-                    label2s: None,
+                    labels: None,
+                    ..Default::default()
                 },
                 Item {
                     nest: Some(0),
                     mark: Some("!".into()),
                     memo: Some("bravo1\nbravo2".into()),
-                    label1s: None,
-                    label2s: None,
+                    labels: None,
+                    ..Default::default()
                 },
                 Item {
                     nest: Some(0),
                     mark: Some("x".into()),
                     memo: Some("charlie1\ncharlie2".into()),
-                    label1s: None,
-                    label2s: None,
+                    labels: None,
+                    ..Default::default()
                 },
             ]
         }
@@ -332,7 +664,7 @@ mod tests {
                 [x] charlie1
                     charlie2
             "};
-            let actual = load_items_via_str(&str).unwrap();
+            let actual = load_items_via_str(str).unwrap();
             assert_eq!(actual, expect());
         }
 
@@ -349,7 +681,7 @@ mod tests {
                 [x] charlie1
                     charlie2
             "};
-            let actual = load_items_via_str(&str).unwrap();
+            let actual = load_items_via_str(str).unwrap();
             assert_eq!(actual, expect());
         }
 
@@ -368,27 +700,357 @@ mod tests {
                 nest: Some(0),
                 mark: Some(" ".into()),
                 memo: Some("plus".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
             Item {
                 nest: Some(0),
                 mark: Some(" ".into()),
                 memo: Some("minus".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
             Item {
                 nest: Some(0),
                 mark: Some(" ".into()),
                 memo: Some("asterisk".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
         ];
         assert_eq!(actual, expect);
     }
 
+    #[test]
+    fn test_cst() {
+        let str = "\n  • ［✓］ foo \r\n      goo\t\r\n";
+        let actual = load_items_via_str(str).unwrap();
+        let expect = vec![
+            Item {
+                nest: Some(0),
+                mark: Some("x".into()),
+                memo: Some("foo\ngoo".into()),
+                labels: None,
+                ..Default::default()
+            },
+        ];
+        assert_eq!(actual, expect);
+        let cst = actual[0].cst.as_ref().unwrap();
+        assert_eq!(cst.lead, "\n");
+        assert_eq!(cst.indent, "  ");
+        assert_eq!(cst.list_marker, "•");
+        assert_eq!(cst.checkbox_open, "［");
+        assert_eq!(cst.checkbox_mark, "✓");
+        assert_eq!(cst.checkbox_shut, "］");
+        assert_eq!(cst.memo_suffix, " ");
+        assert_eq!(cst.line_ending, "\r\n");
+        assert_eq!(cst.continuation_lines, vec![LineCst::new("      goo\t", "\r\n")]);
+        assert_eq!(cst.to_string(), str);
+    }
+
+    #[test]
+    fn test_nest_with_list_markers() {
+        let str = indoc!{"
+            - [ ] foo
+              - [ ] goo
+                    hoo
+                * [ ] joo
+              - [ ] koo
+            [ ] loo
+        "};
+        let actual: Vec<_> = load_items_via_str(str).unwrap().iter().map(|x| x.nest.unwrap()).collect();
+        assert_eq!(actual, vec![0, 1, 2, 1, 0]);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let str = indoc!{"
+            - [ ] foo
+                  [xx] goo
+            - [] hoo
+              ] joo
+            - [x koo
+            ［ loo
+        "};
+        let (items, diagnostics) = load_items_with_diagnostics_via_str(str).unwrap();
+        assert_eq!(items.len(), 1);
+        let actual: Vec<(String, Option<String>)> = diagnostics.iter().map(|x| (x.to_string(), x.fix.clone())).collect();
+        let expect = vec![
+            ("2:7: warning: checkbox has more than one mark".into(), Some("[x]".into())),
+            ("3:3: warning: checkbox has no mark".into(), Some("[ ]".into())),
+            ("4:3: warning: checkbox is not opened".into(), Some("[ ]".into())),
+            ("5:3: warning: checkbox is not shut".into(), Some("[x]".into())),
+            ("6:1: warning: checkbox is not shut".into(), Some("［ ］ ".into())),
+        ];
+        assert_eq!(actual, expect);
+        assert_eq!(diagnostics[0].span, 16..20);
+        assert_eq!(load_items_via_str(str).unwrap(), items);
+    }
+
+    #[test]
+    fn test_spans() {
+        let str = "\n[ ] foo\r\n    goo\r\n\n  - [ ] hoo\n\njoo";
+        let actual: Vec<Span> = load_items_via_str(str).unwrap().into_iter().map(|x| x.span.unwrap()).collect();
+        let expect = vec![
+            Span { lines: 2..=3, bytes: 1..19 },
+            Span { lines: 5..=5, bytes: 20..32 },
+        ];
+        assert_eq!(actual, expect);
+        assert_eq!(expect[0].text(str), "[ ] foo\r\n    goo\r\n");
+        assert_eq!(expect[1].text(str), "  - [ ] hoo\n");
+    }
+
+    #[test]
+    fn test_item_reader() {
+        let str = indoc!{"
+
+            - [ ] Call friends to
+                  ask who's coming
+                  #personal
+
+              - [ ] Call Alice
+            Notes
+
+            - [x] Send invitations
+              1. Print them
+              2. Post them
+
+
+        "};
+        let actual: Vec<Item> = ItemReader::new(str.as_bytes()).map(Result::unwrap).collect();
+        let expect = load_items_via_str(str).unwrap();
+        assert_eq!(actual, expect);
+        let actual_csts: Vec<_> = actual.iter().map(|x| x.cst.clone()).collect();
+        let expect_csts: Vec<_> = expect.iter().map(|x| x.cst.clone()).collect();
+        assert_eq!(actual_csts, expect_csts);
+        let actual_spans: Vec<_> = actual.iter().map(|x| x.span.clone()).collect();
+        let expect_spans: Vec<_> = expect.iter().map(|x| x.span.clone()).collect();
+        assert_eq!(actual_spans, expect_spans);
+    }
+
+    #[test]
+    fn test_item_reader_with_paragraphs_and_fenced_code() {
+        let str = indoc!{"
+            - [ ] Write the guide
+
+                  It has two paragraphs,
+                  and an example:
+
+                  ```
+                  - [ ] not an item
+
+                  ```
+              - [ ] Review it
+
+            Notes
+            - [x] Publish it
+                  ~~~
+                  [ ] not closed
+
+
+        "};
+        let actual: Vec<Item> = ItemReader::new(str.as_bytes()).map(Result::unwrap).collect();
+        let expect = load_items_via_str(str).unwrap();
+        assert_eq!(actual.len(), 3);
+        assert_eq!(actual, expect);
+        let actual_csts: Vec<_> = actual.iter().map(|x| x.cst.clone()).collect();
+        let expect_csts: Vec<_> = expect.iter().map(|x| x.cst.clone()).collect();
+        assert_eq!(actual_csts, expect_csts);
+        assert_eq!(actual[2].cst.as_ref().unwrap().lead, "\nNotes\n");
+        assert_eq!(actual[0].memo, Some("Write the guide\n\nIt has two paragraphs,\nand an example:\n\n```\n- [ ] not an item\n\n```".into()));
+    }
+
+    #[test]
+    fn test_item_reader_with_headings() {
+        let str = indoc!{"
+            Party
+            =====
+
+            - [ ] Book venue
+            # Work
+            - [ ] foo
+            Setext is lazy text
+            ---
+
+            Website
+            -------
+                - [ ] goo
+
+            # Done
+
+        "};
+        let actual: Vec<Item> = ItemReader::new(str.as_bytes()).map(Result::unwrap).collect();
+        let expect = load_items_via_str(str).unwrap();
+        assert_eq!(actual.len(), 3);
+        assert_eq!(actual, expect);
+        let actual_csts: Vec<_> = actual.iter().map(|x| x.cst.clone()).collect();
+        let expect_csts: Vec<_> = expect.iter().map(|x| x.cst.clone()).collect();
+        assert_eq!(actual_csts, expect_csts);
+        let actual_sections: Vec<_> = actual.iter().map(|x| x.section.clone()).collect();
+        let expect_sections: Vec<_> = expect.iter().map(|x| x.section.clone()).collect();
+        assert_eq!(actual_sections, expect_sections);
+        assert_eq!(actual[2].section, Some(vec!["Work".into(), "Website".into()]));
+        assert_eq!(ItemReader::new("# Work\n\nWebsite\n---\n".as_bytes()).count(), 0);
+    }
+
+    #[test]
+    fn test_item_reader_with_long_text() {
+        let mut str = String::new();
+        for i in 0..1000 {
+            str.push_str(&format!("# Part {}\n\nProse\nabout it\n\n- [ ] foo {}\n  more\n\n  even more\n<!--\n[ ] hidden\n\n-->\n", i, i));
+        }
+        let mut reader = ItemReader::new(str.as_bytes());
+        let mut actual = Vec::new();
+        let mut max_len = 0;
+        while let Some(item) = reader.next() {
+            actual.push(item.unwrap());
+            max_len = max_len.max(reader.text.len());
+        }
+        let expect = load_items_via_str(&str).unwrap();
+        assert_eq!(actual.len(), 1000);
+        assert_eq!(actual, expect);
+        let actual_spans: Vec<_> = actual.iter().map(|x| x.span.clone()).collect();
+        let expect_spans: Vec<_> = expect.iter().map(|x| x.span.clone()).collect();
+        assert_eq!(actual_spans, expect_spans);
+        assert!(max_len < 200, "the reader keeps {} bytes of text", max_len);
+    }
+
+    #[test]
+    fn test_load_list_via_str() {
+        let str = indoc!{"
+            ---
+            title: Party
+            labels: #personal
+            ---
+            [ ] foo
+        "};
+        let (meta, items) = load_list_via_str(str).unwrap();
+        assert_eq!(meta.title(), Some("Party"));
+        assert_eq!(meta.labels(), vec![Label::new(["personal"])]);
+        assert_eq!(items, load_items_via_str(str).unwrap());
+        assert_eq!(items[0].labels, None);
+        let (meta, items) = load_list_via_str("---\ntitle: Party\n---\n").unwrap();
+        assert_eq!(meta.title(), Some("Party"));
+        assert_eq!(items, vec![]);
+    }
+
+    #[test]
+    fn test_item_reader_with_front_matter() {
+        let str = indoc!{"
+            ---
+            title: Party
+            owner: Alice
+            ---
+
+            # Work
+            - [ ] foo
+            ---
+            title: not front matter
+            ---
+        "};
+        let actual: Vec<Item> = ItemReader::new(str.as_bytes()).map(Result::unwrap).collect();
+        let expect = load_items_via_str(str).unwrap();
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual, expect);
+        let actual_csts: Vec<_> = actual.iter().map(|x| x.cst.clone()).collect();
+        let expect_csts: Vec<_> = expect.iter().map(|x| x.cst.clone()).collect();
+        assert_eq!(actual_csts, expect_csts);
+        assert_eq!(actual[0].span, expect[0].span);
+        assert_eq!(ItemReader::new("---\ntitle: Party\n---\n\n".as_bytes()).count(), 0);
+    }
+
+    #[test]
+    fn test_load_items_via_path_with_includes() {
+        let dir = TestDir::new("load-includes");
+        ::std::fs::create_dir_all(dir.join("areas")).unwrap();
+        ::std::fs::write(dir.join("todo.txt"), "[ ] inbox\n#include:areas/work.txt\n[ ] later\n#include:areas/home.txt\n").unwrap();
+        ::std::fs::write(dir.join("areas/work.txt"), "# Work\n[ ] foo\n#include:../shared.txt\n").unwrap();
+        ::std::fs::write(dir.join("areas/home.txt"), "[ ] hoo\n").unwrap();
+        ::std::fs::write(dir.join("shared.txt"), "[ ] goo\n").unwrap();
+        let items = load_items_via_path(&dir.join("todo.txt")).unwrap();
+        let actual: Vec<_> = items.iter().map(|x| (x.memo.clone().unwrap(), x.file.clone().unwrap())).collect();
+        assert_eq!(actual, vec![
+            ("inbox".into(), dir.join("todo.txt")),
+            ("foo".into(), dir.join("areas/work.txt")),
+            ("goo".into(), dir.join("areas/../shared.txt")),
+            ("later".into(), dir.join("todo.txt")),
+            ("hoo".into(), dir.join("areas/home.txt")),
+        ]);
+        assert_eq!(items[1].section, Some(vec!["Work".into()]));
+        assert_eq!(items[2].section, None);
+        let (meta, list_items) = load_list_via_path(&dir.join("todo.txt")).unwrap();
+        assert_eq!(meta, ListMeta::default());
+        assert_eq!(list_items, items);
+    }
+
+    #[test]
+    fn test_load_items_via_path_with_missing_include_and_cycle() {
+        let dir = TestDir::new("load-include-errors");
+        ::std::fs::create_dir_all(dir.join("areas")).unwrap();
+        ::std::fs::write(dir.join("todo.txt"), "[ ] foo\n#include:missing.txt\n#include:areas/work.txt\n").unwrap();
+        ::std::fs::write(dir.join("areas/work.txt"), "[ ] goo\n#include:../todo.txt\n[xx] hoo\n").unwrap();
+        let (items, diagnostics) = load_items_with_diagnostics_via_path(&dir.join("todo.txt")).unwrap();
+        let memos: Vec<_> = items.iter().map(|x| x.memo.clone().unwrap()).collect();
+        assert_eq!(memos, vec!["foo", "goo"]);
+        let work = dir.join("areas/work.txt");
+        let actual: Vec<String> = diagnostics.iter().map(|x| x.to_string()).collect();
+        assert_eq!(actual, vec![
+            format!("2:10: error: included file is not found: `{}`", dir.join("missing.txt").display()),
+            format!("{}:3:1: warning: checkbox has more than one mark", work.display()),
+            format!("{}:2:10: error: include cycle: `{}` -> `{}` -> `{}`", work.display(), dir.join("todo.txt").display(), work.display(), dir.join("areas/../todo.txt").display()),
+        ]);
+        assert_eq!(load_items_via_path(&dir.join("todo.txt")).unwrap_err().kind(), ::std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_item_reader_with_prose_and_comments() {
+        let str = indoc!{"
+            Plans for the party.
+
+            - [ ] Call Alice
+            // ask about food
+            - [ ] Book venue
+            <!--
+            - [ ] not an item
+            -->
+
+            See you there!
+            <!-- not closed
+            - [ ] not an item
+
+        "};
+        let actual: Vec<Item> = ItemReader::new(str.as_bytes()).map(Result::unwrap).collect();
+        let expect = load_items_via_str(str).unwrap();
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual, expect);
+        let actual_csts: Vec<_> = actual.iter().map(|x| x.cst.clone()).collect();
+        let expect_csts: Vec<_> = expect.iter().map(|x| x.cst.clone()).collect();
+        assert_eq!(actual_csts, expect_csts);
+        assert_eq!(ItemReader::new("Just prose\n\n// and a comment\n".as_bytes()).count(), 0);
+    }
+
+    #[test]
+    fn test_item_reader_with_options() {
+        let str = "[ ] foo #goo\n";
+        let options = LoadOptions { strip_labels: true, ..Default::default() };
+        let actual: Vec<Item> = ItemReader::with_options(str.as_bytes(), options).map(Result::unwrap).collect();
+        assert_eq!(actual[0].memo, Some("foo".into()));
+        assert_eq!(actual[0].labels, Some(vec![Label::new(["goo"])]));
+    }
+
+    #[test]
+    fn test_item_reader_with_blank_lines_only() {
+        assert_eq!(ItemReader::new("\n  \n".as_bytes()).count(), 0);
+        assert_eq!(ItemReader::new("".as_bytes()).count(), 0);
+    }
+
+    #[test]
+    fn test_item_reader_with_io_error() {
+        let bytes: &[u8] = b"[ ] foo\n[ ] \xff\n";
+        let actual: Vec<Result<Item, LoadError>> = ItemReader::new(bytes).collect();
+        assert!(matches!(actual.last(), Some(Err(LoadError::Io(_)))));
+    }
+
     #[test]
     fn test_indent_with_spaces() {
         let str = indoc!{"
@@ -402,70 +1064,135 @@ mod tests {
                    [ ] 7-space
                     [ ] 8-space
         "};
-        let actual = load_items_via_str(&str).unwrap();
+        let actual = load_items_via_str(str).unwrap();
         let expect = vec![
             Item {
                 nest: Some(0),
                 mark: Some(" ".into()),
                 memo: Some("0-space".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
             Item {
                 nest: Some(0),
                 mark: Some(" ".into()),
                 memo: Some("1-space".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
             Item {
                 nest: Some(0),
                 mark: Some(" ".into()),
                 memo: Some("2-space".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
             Item {
                 nest: Some(0),
                 mark: Some(" ".into()),
                 memo: Some("3-space".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
             Item {
                 nest: Some(1),
                 mark: Some(" ".into()),
                 memo: Some("4-space".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
             Item {
                 nest: Some(1),
                 mark: Some(" ".into()),
                 memo: Some("5-space".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
             Item {
                 nest: Some(1),
                 mark: Some(" ".into()),
                 memo: Some("6-space".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
             Item {
                 nest: Some(1),
                 mark: Some(" ".into()),
                 memo: Some("7-space".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
             Item {
                 nest: Some(2),
                 mark: Some(" ".into()),
                 memo: Some("8-space".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
+            },
+        ];
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn test_labels() {
+        let str = indoc!{"
+            [ ] Call friends to
+                ask who's coming
+                #personal
+                #priority:1
+            [ ] foo #a #b:c #a #b:c #project:website:design
+        "};
+        let actual = load_items_via_str(str).unwrap();
+        let expect = vec![
+            Item {
+                nest: Some(0),
+                mark: Some(" ".into()),
+                memo: Some("Call friends to\nask who's coming\n#personal\n#priority:1".into()),
+                labels: Some(vec![
+                    Label::new(["personal"]),
+                    Label::new(["priority", "1"]),
+                ]),
+                ..Default::default()
+            },
+            Item {
+                nest: Some(0),
+                mark: Some(" ".into()),
+                memo: Some("foo #a #b:c #a #b:c #project:website:design".into()),
+                labels: Some(vec![
+                    Label::new(["a"]),
+                    Label::new(["b", "c"]),
+                    Label::new(["project", "website", "design"]),
+                ]),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(actual, expect);
+    }
+
+    #[test]
+    fn test_labels_with_options() {
+        let str = indoc!{"
+            [ ] Call friends to
+                ask who's coming
+                #personal
+                #priority:1 #personal
+        "};
+        let options = LoadOptions {
+            label_dedup: LabelDedup::Keep,
+            strip_labels: true,
+        };
+        let actual = load_items_via_buf_read_with_options(str.as_bytes(), &options).unwrap();
+        let expect = vec![
+            Item {
+                nest: Some(0),
+                mark: Some(" ".into()),
+                memo: Some("Call friends to\nask who's coming".into()),
+                labels: Some(vec![
+                    Label::new(["personal"]),
+                    Label::new(["priority", "1"]),
+                    Label::new(["personal"]),
+                ]),
+                ..Default::default()
             },
         ];
         assert_eq!(actual, expect);
@@ -478,28 +1205,28 @@ mod tests {
             \t[ ] 1-tab
             \t\t[ ] 2-tab
         "};
-        let actual = load_items_via_str(&str).unwrap();
+        let actual = load_items_via_str(str).unwrap();
         let expect = vec![
             Item {
                 nest: Some(0),
                 mark: Some(" ".into()),
                 memo: Some("0-tab".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
             Item {
                 nest: Some(1),
                 mark: Some(" ".into()),
                 memo: Some("1-tab".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
             Item {
                 nest: Some(2),
                 mark: Some(" ".into()),
                 memo: Some("2-tab".into()),
-                label1s: None,
-                label2s: None,
+                labels: None,
+                ..Default::default()
             },
         ];
         assert_eq!(actual, expect);
@@ -2,16 +2,23 @@
 //use indoc::indoc;
 use std::path::Path;
 
-mod item;
-mod load;
 mod ui;
 
-use load::*;
+use todoby::load::*;
 //use crate::item::item::Item;
   
 fn main() {
-    let items = load_items_via_path(Path::new("todo.txt"));
-    println!("{:?}", items);
+    let path = Path::new("todo.txt");
+    match std::fs::read_to_string(path).and_then(|str| {
+        let (items, diagnostics) = load_items_with_diagnostics_via_str(&str)?;
+        for diagnostic in diagnostics {
+            eprint!("{}", diagnostic.render(&path.display().to_string(), &str));
+        }
+        Ok(items)
+    }) {
+        Ok(items) => println!("{:?}", items),
+        Err(e) => eprintln!("{}: {}", path.display(), e),
+    }
 }
 
 
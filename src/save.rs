@@ -0,0 +1,1046 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::io::Write;
+use chrono::NaiveDateTime;
+use crate::item::document::{Document, Part};
+use crate::item::item::Item;
+use crate::item::item_parser::{self, lead_headings};
+use crate::item::node::Node;
+use crate::item::meta::ListMeta;
+use crate::item::render::{render_item, RenderOptions};
+use crate::item::section::SectionPath;
+use crate::load::{load_documents_with_diagnostics_via_path, Documents};
+
+/// Save items via path.
+///
+/// Each item is saved to the file that it was loaded from, such as a
+/// file that is included by the file of the path, so that the include
+/// directives still include the same items. A new item is saved to the
+/// file of the loaded item before it, or else after it, or else to the
+/// file of the path.
+///
+/// The file of the path and each file that it includes are written,
+/// even if they have no items left, and each keeps the text between
+/// its items, such as its headings and include directives, as the file
+/// is now, with the items in the places of its items in order,
+/// as in `Document::set_items`. A file that no item is saved to and
+/// that would have no text left is not written, so it is not emptied.
+///
+/// If no item was loaded from the path or the files that it includes,
+/// and the items were loaded from another file, then this saves as:
+/// the text of that file and the items that were loaded from it are
+/// written to the path, and that file is not written.
+///
+/// ```
+/// let path = Path::new("example.txt");
+/// save_items_via_path(&items, path).unwrap();
+/// ```
+///
+pub fn save_items_via_path(items: &[Item], path: &Path) -> ::std::io::Result<()> {
+    save_items_via_path_with_options(items, path, &SaveOptions::default())
+}
+
+/// Save items via path with options, as in `save_items_via_path`.
+///
+/// ```
+/// let path = Path::new("example.txt");
+/// let options = SaveOptions { normalize_dates: Some(Local::now().naive_local()), ..Default::default() };
+/// save_items_via_path_with_options(&items, path, &options).unwrap();
+/// ```
+///
+pub fn save_items_via_path_with_options(items: &[Item], path: &Path, options: &SaveOptions) -> ::std::io::Result<()> {
+    save_items_to_documents_via_path(None, items, path, options)
+}
+
+/// Save items, and the metadata of the file of the path if any, to the
+/// documents of the path and of the files it includes, as in `save_items_via_path`.
+fn save_items_to_documents_via_path(meta: Option<&ListMeta>, items: &[Item], path: &Path, options: &SaveOptions) -> ::std::io::Result<()> {
+    let mut documents = load_documents_or_default_via_path(path)?;
+    // Save as: the items were loaded from another file, so the document of
+    // that file is saved to the path, with its items, instead of to itself.
+    let mut items = items.to_vec();
+    if let Some(root) = root_of_items_via_other_path(&items, &documents) {
+        documents = load_documents_or_default_via_path(&root)?;
+        documents[0].0 = path.to_path_buf();
+        for item in items.iter_mut().filter(|x| x.file.as_ref() == Some(&root)) {
+            item.file = Some(path.to_path_buf());
+        }
+    }
+    if let Some(meta) = meta {
+        documents[0].1.meta = meta.clone();
+    }
+    // A file that is included more than once is saved once.
+    let mut seen: Vec<PathBuf> = Vec::new();
+    documents.retain(|(x, _)| !seen.contains(x) && { seen.push(x.clone()); true });
+    let mut groups = group_items_by_file(&items, path);
+    // A file of items that is not included, such as a file that items were loaded from before.
+    for (file, _) in groups.iter() {
+        if !documents.iter().any(|(x, _)| x == file) {
+            let document = match ::std::fs::read_to_string(file) {
+                Ok(source) => item_parser::parse_document(&source).0,
+                Err(e) if e.kind() == ::std::io::ErrorKind::NotFound => Document::default(),
+                Err(e) => return Err(e),
+            };
+            documents.push((file.clone(), document));
+        }
+    }
+    for (file, mut document) in documents {
+        let items = match groups.iter().position(|(x, _)| *x == file) {
+            Some(at) => groups.remove(at).1,
+            None => Vec::new(),
+        };
+        let is_without_items = items.is_empty();
+        document.set_items(items);
+        // A file that no item is saved to is not emptied.
+        if is_without_items && document.parts.is_empty() && document.meta.to_front_matter().is_empty() && file.exists() {
+            continue;
+        }
+        save_document_via_path_with_options(&document, &file, options)?;
+    }
+    Ok(())
+}
+
+/// Load the documents of a path and of the files that it includes,
+/// or else an empty document for the path if its file is not found.
+fn load_documents_or_default_via_path(path: &Path) -> ::std::io::Result<Documents> {
+    match load_documents_with_diagnostics_via_path(path) {
+        Ok((documents, _)) => Ok(documents),
+        Err(e) if e.kind() == ::std::io::ErrorKind::NotFound => Ok(vec![(path.to_path_buf(), Document::default())]),
+        Err(e) => Err(e),
+    }
+}
+
+/// Get the file that items were loaded from, if it is another file than
+/// the files of the documents, which is the file of the first item that
+/// has one, when no item has the file of a document.
+fn root_of_items_via_other_path(items: &[Item], documents: &Documents) -> Option<PathBuf> {
+    let is_loaded_via_documents = items.iter()
+        .filter_map(|x| x.file.as_ref())
+        .any(|file| documents.iter().any(|(x, _)| x == file));
+    if is_loaded_via_documents {
+        return None;
+    }
+    items.iter().find_map(|x| x.file.clone())
+}
+
+/// Group items by the file to save them to, as in `save_items_via_path`,
+/// in the order that each file first appears.
+fn group_items_by_file(items: &[Item], path: &Path) -> Vec<(PathBuf, Vec<Item>)> {
+    let mut groups: Vec<(PathBuf, Vec<Item>)> = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        let file = items[..=i].iter().rev().chain(&items[i + 1..])
+            .find_map(|x| x.file.clone())
+            .unwrap_or_else(|| path.to_path_buf());
+        match groups.iter_mut().find(|(x, _)| *x == file) {
+            Some((_, items)) => items.push(item.clone()),
+            None => groups.push((file, vec![item.clone()])),
+        }
+    }
+    groups
+}
+
+/// Save items via file.
+///
+/// ```
+/// let file = File::create("example.txt").unwrap();
+/// save_items_via_file(&items, file).unwrap();
+/// ```
+///
+pub fn save_items_via_file(items: &[Item], file: File) -> ::std::io::Result<()> {
+    save_items_via_write(items, ::std::io::BufWriter::new(file))
+}
+
+/// Save items via write.
+///
+/// ```
+/// let mut write: Vec<u8> = Vec::new();
+/// save_items_via_write(&items, &mut write).unwrap();
+/// ```
+///
+pub fn save_items_via_write(items: &[Item], mut write: impl ::std::io::Write) -> ::std::io::Result<()> {
+    write.write_all(save_items_via_string(items).as_bytes())?;
+    write.flush()
+}
+
+/// Options for saving items.
+#[derive(Debug, Default, Clone)]
+pub struct SaveOptions {
+    /// Rewrite relative dates, such as `#due:tomorrow`, to absolute
+    /// dates relative to this time, so their meaning does not drift.
+    pub normalize_dates: Option<NaiveDateTime>,
+    /// Style to write new items in, such as with a list marker.
+    pub render: RenderOptions,
+}
+
+/// Save items via write with options.
+///
+/// ```
+/// let mut write: Vec<u8> = Vec::new();
+/// let options = SaveOptions { normalize_dates: Some(Local::now().naive_local()), ..Default::default() };
+/// save_items_via_write_with_options(&items, &mut write, &options).unwrap();
+/// ```
+///
+pub fn save_items_via_write_with_options(items: &[Item], mut write: impl ::std::io::Write, options: &SaveOptions) -> ::std::io::Result<()> {
+    write.write_all(save_items_via_string_with_options(items, options).as_bytes())?;
+    write.flush()
+}
+
+/// Save items via string with options.
+///
+/// ```
+/// let items = load_items_via_str("[ ] foo #due:tomorrow\n").unwrap();
+/// let options = SaveOptions { normalize_dates: Some(now), ..Default::default() };
+/// let str = save_items_via_string_with_options(&items, &options);
+/// assert_eq!(str, "[ ] foo #due:2026-10-17\n");
+/// ```
+///
+pub fn save_items_via_string_with_options(items: &[Item], options: &SaveOptions) -> String {
+    save_document_via_string_with_options(&Document::from_items(items.to_vec()), options)
+}
+
+/// Save items via string.
+///
+/// A loaded item that has not changed is written exactly as it was loaded.
+/// A loaded item that has changed keeps as much of its original text as
+/// it can, by changing only the tokens of its concrete syntax tree that
+/// are for the changes, such as the mark or the memo lines.
+/// A new item is written in the default style of `RenderOptions`,
+/// or in the style of `SaveOptions::render`, indented by its nest.
+///
+/// Each loaded item keeps the text around it as it was loaded, such as
+/// front matter, headings, and prose before it, and is written with it,
+/// so items that are loaded and saved round-trip.
+///
+/// ```
+/// let mut items = load_items_via_str("- [ ] foo\n\n- [ ] goo\n").unwrap();
+/// items[0].mark = Some(Status::Done);
+/// let str = save_items_via_string(&items);
+/// assert_eq!(str, "- [x] foo\n\n- [ ] goo\n");
+/// ```
+///
+pub fn save_items_via_string(items: &[Item]) -> String {
+    save_items_via_string_with_options(items, &SaveOptions::default())
+}
+
+/// Save a list, which is its metadata and its items, via path.
+///
+/// The metadata is saved to the file of the path, and each item is
+/// saved to its file, as in `save_items_via_path`.
+///
+/// ```
+/// let path = Path::new("example.txt");
+/// save_list_via_path(&meta, &items, path).unwrap();
+/// ```
+///
+pub fn save_list_via_path(meta: &ListMeta, items: &[Item], path: &Path) -> ::std::io::Result<()> {
+    save_items_to_documents_via_path(Some(meta), items, path, &SaveOptions::default())
+}
+
+/// Save a list, which is its metadata and its items, via write.
+///
+/// ```
+/// let mut write: Vec<u8> = Vec::new();
+/// save_list_via_write(&meta, &items, &mut write).unwrap();
+/// ```
+///
+pub fn save_list_via_write(meta: &ListMeta, items: &[Item], mut write: impl ::std::io::Write) -> ::std::io::Result<()> {
+    write.write_all(save_list_via_string(meta, items).as_bytes())?;
+    write.flush()
+}
+
+/// Save a list, which is its metadata and its items, via string.
+///
+/// The metadata is written as front matter at the start, exactly as it
+/// was loaded if it has not changed, instead of any front matter of the
+/// items, and the items are written after it as in `save_items_via_string`.
+///
+/// ```
+/// let (mut meta, items) = load_list_via_str("---\ntitle: Party\n---\n[ ] foo\n").unwrap();
+/// meta.set("owner", Some("Alice"));
+/// let str = save_list_via_string(&meta, &items);
+/// assert_eq!(str, "---\ntitle: Party\nowner: Alice\n---\n[ ] foo\n");
+/// ```
+///
+pub fn save_list_via_string(meta: &ListMeta, items: &[Item]) -> String {
+    let mut document = Document::from_items(items.to_vec());
+    document.meta = meta.clone();
+    save_document_via_string(&document)
+}
+
+/// Save a document via path.
+///
+/// This writes only the file of the path; use `save_items_via_path`
+/// to save items to the files that they were loaded from.
+///
+/// ```
+/// let path = Path::new("example.txt");
+/// save_document_via_path(&document, path).unwrap();
+/// ```
+///
+pub fn save_document_via_path(document: &Document, path: &Path) -> ::std::io::Result<()> {
+    save_document_via_path_with_options(document, path, &SaveOptions::default())
+}
+
+/// Save a document via path with options, as in `save_document_via_path`.
+///
+/// ```
+/// let path = Path::new("example.txt");
+/// let options = SaveOptions { normalize_dates: Some(Local::now().naive_local()), ..Default::default() };
+/// save_document_via_path_with_options(&document, path, &options).unwrap();
+/// ```
+///
+pub fn save_document_via_path_with_options(document: &Document, path: &Path, options: &SaveOptions) -> ::std::io::Result<()> {
+    let mut write = ::std::io::BufWriter::new(::std::fs::File::create(path)?);
+    write.write_all(save_document_via_string_with_options(document, options).as_bytes())?;
+    write.flush()
+}
+
+/// Save a document via write.
+///
+/// ```
+/// let mut write: Vec<u8> = Vec::new();
+/// save_document_via_write(&document, &mut write).unwrap();
+/// ```
+///
+pub fn save_document_via_write(document: &Document, mut write: impl ::std::io::Write) -> ::std::io::Result<()> {
+    write.write_all(save_document_via_string(document).as_bytes())?;
+    write.flush()
+}
+
+/// Save a document via string with options.
+///
+/// ```
+/// let options = SaveOptions { normalize_dates: Some(now), ..Default::default() };
+/// let str = save_document_via_string_with_options(&document, &options);
+/// ```
+///
+pub fn save_document_via_string_with_options(document: &Document, options: &SaveOptions) -> String {
+    match options.normalize_dates {
+        Some(now) => {
+            let mut document = document.clone();
+            for item in document.items_mut() {
+                item.normalize_dates(now);
+            }
+            save_document(&document, &options.render)
+        },
+        None => save_document(document, &options.render),
+    }
+}
+
+/// Save a document via string.
+///
+/// The metadata is written as front matter at the start, as in
+/// `save_list_via_string`, then the items as in `save_items_via_string`,
+/// with the text between them as it was loaded, such as headings,
+/// include directives, comments, and prose.
+///
+/// A new item is written after headings for its section if the text
+/// before it is in another section, such as `## Mobile` for the section
+/// `Work` / `Mobile` after the headings `# Work` and `## Website`, or
+/// `# Work` again for the section `Work`, as in `SectionPath::headings_to`.
+/// A new item without a section is written before the first heading.
+///
+/// ```
+/// let (mut document, _) = parse_document("Intro\n\n# Work\n[ ] foo\n[ ] goo\n");
+/// document.remove_item(0);
+/// assert_eq!(save_document_via_string(&document), "Intro\n\n# Work\n[ ] goo\n");
+/// ```
+///
+pub fn save_document_via_string(document: &Document) -> String {
+    save_document(document, &RenderOptions::default())
+}
+
+/// Save a document via string, with new items in the style of render options.
+fn save_document(document: &Document, render: &RenderOptions) -> String {
+    let mut s = document.meta.to_front_matter();
+    let mut path = SectionPath::default();
+    // Where to write a new item without a section, which is before the
+    // blank lines before the first heading, because a heading can not be closed.
+    let mut before_headings: Option<usize> = None;
+    for part in document.parts.iter() {
+        match part {
+            Part::Item(item) if item.cst.is_none() => {
+                let section = item.section.as_deref().unwrap_or_default();
+                match before_headings {
+                    Some(at) if section.is_empty() => {
+                        let item_str = item_to_string(item, render);
+                        s.insert_str(at, &item_str);
+                        before_headings = Some(at + item_str.len());
+                    },
+                    _ => {
+                        if path.headings.is_empty() && !path.headings_to(section).is_empty() {
+                            before_headings = Some(s.len());
+                        }
+                        path.write_headings_to(section, &mut s);
+                        s.push_str(&item_to_string(item, render));
+                    },
+                }
+            },
+            Part::Item(item) => s.push_str(&item_to_string(item, render)),
+            Part::Text(text) => {
+                if before_headings.is_none() {
+                    before_headings = first_heading_start(text).map(|x| s.len() + x);
+                }
+                for heading in lead_headings(text) {
+                    path.push(heading);
+                }
+                s.push_str(text);
+            },
+        }
+    }
+    s
+}
+
+/// Get the start of the blank lines before the first heading of text,
+/// as in `lead_headings`.
+fn first_heading_start(text: &str) -> Option<usize> {
+    let mut input = nom::combinator::opt(item_parser::front_matter)(text).map_or(text, |(rest, _)| rest);
+    while let Ok((rest, _)) = item_parser::blank_lines(input) {
+        match item_parser::lead_node(rest) {
+            Ok((_, Node::Heading(_))) => return Some(text.len() - input.len()),
+            Ok((rest, _)) if rest.len() < input.len() => input = rest,
+            _ => break,
+        }
+    }
+    None
+}
+
+/// Save one item into the source text that it was loaded from,
+/// by replacing only the text of its span.
+///
+/// The rest of the source text is kept as-is, so this works even if
+/// other parts of the source text have changed, as long as the text
+/// before the item has the same length.
+///
+/// Returns `None` if the item was not loaded, or if its span is not
+/// in the source text.
+///
+/// ```
+/// let str = "[ ] foo\n[ ] goo\n";
+/// let mut items = load_items_via_str(str).unwrap();
+/// items[1].mark = Some(Status::Done);
+/// let str = save_item_via_splice(str, &items[1]).unwrap();
+/// assert_eq!(str, "[ ] foo\n[x] goo\n");
+/// ```
+///
+pub fn save_item_via_splice(source: &str, item: &Item) -> Option<String> {
+    let span = item.span.as_ref()?;
+    let mut cst = item.cst.clone()?;
+    if item.is_changed() {
+        cst.update(item);
+    }
+    let mut s = String::from(source.get(..span.bytes.start)?);
+    s.push_str(&cst.body());
+    s.push_str(source.get(span.bytes.end..)?);
+    Some(s)
+}
+
+/// Convert one item to its text, including its blank lines.
+fn item_to_string(item: &Item, render: &RenderOptions) -> String {
+    match &item.cst {
+        Some(cst) if !item.is_changed() => cst.to_string(),
+        Some(cst) => {
+            let mut cst = cst.clone();
+            cst.update(item);
+            cst.to_string()
+        },
+        None => render_item(item, render) + "\n",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::status::Status;
+    use crate::load::*;
+    use crate::test_dir::TestDir;
+    use indoc::indoc;
+
+    fn assert_round_trip(str: &str) {
+        let items = load_items_via_str(str).unwrap();
+        assert_eq!(save_items_via_string(&items), str);
+        let document = load_document_via_str(str).unwrap();
+        assert_eq!(save_document_via_string(&document), str);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        assert_round_trip(indoc!{"
+            [ ] foo
+            [!] goo
+            [x] hoo
+        "});
+    }
+
+    #[test]
+    fn test_round_trip_with_blank_lines() {
+        assert_round_trip(indoc!{"
+
+
+            [ ] alpha1
+                alpha2
+
+            [!] bravo1
+
+
+            [x] charlie1
+
+
+        "});
+    }
+
+    #[test]
+    fn test_round_trip_with_readme_example() {
+        assert_round_trip(indoc!{"
+            - [ ] Call friends to
+                  ask who's coming
+                  #personal
+                  #priority:1
+
+              - [ ] Call Alice
+
+            • [@] Arrange flowers; delegate to the
+                  florist who will deliver on the day
+            \t［✓］ Tab and full-width\t
+        "});
+    }
+
+    #[test]
+    fn test_round_trip_with_ordered_list_markers_and_notes() {
+        assert_round_trip(indoc!{"
+            1. [ ] foo
+               - a note
+                 that goes on
+            2) [x] goo
+               3. another note
+        "});
+    }
+
+    #[test]
+    fn test_save_with_changed_marks_of_ordered_items_and_notes() {
+        let str = indoc!{"
+            1. [ ] foo
+               - a note
+            2. [x] goo
+        "};
+        let mut items = load_items_via_str(str).unwrap();
+        items[0].mark = Some(Status::Done);
+        items[1].mark = Some(Status::Todo);
+        items[2].mark = None;
+        assert_eq!(save_items_via_string(&items), indoc!{"
+            1. [x] foo
+               - [ ] a note
+            2. goo
+        "});
+    }
+
+    #[test]
+    fn test_round_trip_with_paragraphs_and_fenced_code() {
+        assert_round_trip(indoc!{"
+            - [ ] foo
+
+                  goo
+                    indented
+                  ```
+                  - [ ] code
+
+                  ```
+                - [ ] child
+        "});
+    }
+
+    #[test]
+    fn test_save_with_changed_memo_with_paragraphs() {
+        let str = indoc!{"
+            - [ ] foo
+
+                  goo
+                    indented
+            - [ ] hoo
+        "};
+        let mut items = load_items_via_str(str).unwrap();
+        items[0].memo = Some("foo\n\ngoo\n  indented\n\n  more".into());
+        items[0].nest = Some(1);
+        items.push(Item {
+            nest: Some(0),
+            mark: Some(Status::Todo),
+            memo: Some("joo\n\n    code".into()),
+            ..Default::default()
+        });
+        let saved = save_items_via_string(&items);
+        assert_eq!(saved, indoc!{"
+                - [ ] foo
+
+                      goo
+                        indented
+
+                        more
+            - [ ] hoo
+            [ ] joo
+
+                    code
+        "});
+        let memos: Vec<_> = load_items_via_str(&saved).unwrap().into_iter().map(|x| x.memo).collect();
+        let expect: Vec<_> = items.into_iter().map(|x| x.memo).collect();
+        assert_eq!(memos, expect);
+    }
+
+    #[test]
+    fn test_round_trip_with_headings() {
+        assert_round_trip(indoc!{"
+            Inbox
+            =====
+
+            [ ] foo
+            # Work #area
+            - [ ] goo
+
+            ## Website ##
+            - [ ] hoo
+
+            Later
+            -----
+            - [ ] joo
+
+            # Done
+        "});
+    }
+
+    #[test]
+    fn test_save_with_new_items_in_sections() {
+        let str = indoc!{"
+            # Work
+
+            ## Website
+            [ ] foo
+        "};
+        let mut document = load_document_via_str(str).unwrap();
+        let new_item = |memo: &str, section: &[&str]| Item {
+            nest: Some(0),
+            mark: Some(Status::Todo),
+            memo: Some(memo.into()),
+            section: Some(section.iter().map(|x| x.to_string()).collect()),
+            ..Default::default()
+        };
+        document.insert_item(1, new_item("goo", &["Work", "Website"]));
+        document.insert_item(2, new_item("hoo", &["Work", "Mobile", "iOS"]));
+        document.insert_item(3, new_item("joo", &["Home"]));
+        let saved = save_document_via_string(&document);
+        assert_eq!(saved, indoc!{"
+            # Work
+
+            ## Website
+            [ ] foo
+            [ ] goo
+
+            ## Mobile
+
+            ### iOS
+
+            [ ] hoo
+
+            # Home
+
+            [ ] joo
+        "});
+        let sections: Vec<_> = load_items_via_str(&saved).unwrap().into_iter().map(|x| x.section).collect();
+        let expect: Vec<_> = document.into_items().into_iter().map(|x| x.section).collect();
+        assert_eq!(sections, expect);
+    }
+
+    #[test]
+    fn test_save_with_new_items_in_outer_section_and_without_section() {
+        let mut document = load_document_via_str("Intro\n\n# Work\n\n## Website\n[ ] foo\n").unwrap();
+        let new_item = |memo: &str, section: Option<Vec<String>>| Item {
+            nest: Some(0),
+            mark: Some(Status::Todo),
+            memo: Some(memo.into()),
+            section,
+            ..Default::default()
+        };
+        document.insert_item(1, new_item("goo", Some(vec!["Work".into()])));
+        document.insert_item(2, new_item("hoo", None));
+        let saved = save_document_via_string(&document);
+        assert_eq!(saved, "Intro\n[ ] hoo\n\n# Work\n\n## Website\n[ ] foo\n\n# Work\n\n[ ] goo\n");
+        let actual: Vec<_> = load_items_via_str(&saved).unwrap().into_iter().map(|x| (x.memo.unwrap(), x.section)).collect();
+        assert_eq!(actual, vec![
+            ("hoo".into(), None),
+            ("foo".into(), Some(vec!["Work".into(), "Website".into()])),
+            ("goo".into(), Some(vec!["Work".into()])),
+        ]);
+    }
+
+    #[test]
+    fn test_round_trip_with_front_matter() {
+        let str = indoc!{"
+            ---
+            title:   Party
+            marks: [ ] [x]
+            ---
+
+            # Work
+            [ ] foo
+            [x] goo
+        "};
+        assert_round_trip(str);
+        let (meta, items) = load_list_via_str(str).unwrap();
+        assert_eq!(save_list_via_string(&meta, &items), str);
+    }
+
+    #[test]
+    fn test_round_trip_without_items() {
+        // Without items, only a document keeps the text.
+        let assert_document_round_trip = |str: &str| assert_eq!(save_document_via_string(&load_document_via_str(str).unwrap()), str);
+        assert_document_round_trip("hello\n");
+        assert_document_round_trip("---\ntitle: Party\n---\n");
+        assert_document_round_trip("---\ntitle: Party\n---\n\n# Work\n\n");
+        assert_document_round_trip("\n\n");
+        assert_document_round_trip("");
+    }
+
+    #[test]
+    fn test_save_list_with_changed_meta_and_moved_items() {
+        let (mut meta, mut items) = load_list_via_str(indoc!{"
+            ---
+            title: Party
+            ---
+            [ ] foo
+            [ ] goo
+        "}).unwrap();
+        items.swap(0, 1);
+        assert_eq!(save_items_via_string(&items), "---\ntitle: Party\n---\n[ ] goo\n[ ] foo\n");
+        meta.set("owner", Some("Alice"));
+        assert_eq!(save_list_via_string(&meta, &items), "---\ntitle: Party\nowner: Alice\n---\n[ ] goo\n[ ] foo\n");
+        assert_eq!(save_list_via_string(&ListMeta::default(), &items), "[ ] goo\n[ ] foo\n");
+    }
+
+    #[test]
+    fn test_round_trip_with_prose_and_comments() {
+        assert_round_trip(indoc!{"
+            Plans for the party.
+
+            - [ ] Call Alice
+            // ask about food
+            - [x] Book venue
+            <!--
+            - [ ] not an item
+            -->
+
+            See you there!
+        "});
+    }
+
+    #[test]
+    fn test_save_with_changed_items_between_prose_and_comments() {
+        let mut items = load_items_via_str("Intro\n\n[ ] foo\n// comment\n[ ] goo\n\nOutro\n").unwrap();
+        items[0].mark = Some(Status::Done);
+        items.swap(0, 1);
+        assert_eq!(save_items_via_string(&items), "// comment\n[ ] goo\n\nOutro\nIntro\n\n[x] foo\n");
+        let mut document = load_document_via_str("Intro\n\n[ ] foo\n// comment\n[ ] goo\n\nOutro\n").unwrap();
+        document.set_items(items);
+        assert_eq!(save_document_via_string(&document), "Intro\n\n[ ] goo\n// comment\n[x] foo\n\nOutro\n");
+        document.remove_item(0);
+        assert_eq!(save_document_via_string(&document), "Intro\n\n// comment\n[x] foo\n\nOutro\n");
+    }
+
+    #[test]
+    fn test_save_with_render_options_for_new_items() {
+        let mut items = load_items_via_str("* [ ] foo\n").unwrap();
+        items.push(Item { nest: Some(1), mark: Some(Status::Todo), memo: Some("goo #work".into()), ..Default::default() });
+        let options = SaveOptions {
+            render: RenderOptions { list_marker: "*".into(), indent: crate::item::render::Indent::Tabs, ..Default::default() },
+            ..Default::default()
+        };
+        assert_eq!(save_items_via_string_with_options(&items, &options), "* [ ] foo\n\t* [ ] goo #work\n");
+    }
+
+    #[test]
+    fn test_round_trip_with_crlf_and_no_final_line_ending() {
+        assert_round_trip("[ ] foo\r\n    goo\r\n\r\n[x] hoo");
+    }
+
+    #[test]
+    fn test_round_trip_with_strip_labels() {
+        let str = "[ ] foo #goo\n    #hoo:joo\n";
+        let options = LoadOptions { strip_labels: true, ..Default::default() };
+        let items = load_items_via_buf_read_with_options(str.as_bytes(), &options).unwrap();
+        assert_eq!(save_items_via_string(&items), str);
+    }
+
+    #[test]
+    fn test_save_with_changed_mark() {
+        let str = indoc!{"
+            - [ ] Call friends to
+                  ask who's coming
+
+              * [ ] Call Alice
+        "};
+        let mut items = load_items_via_str(str).unwrap();
+        items[0].mark = Some(Status::Done);
+        items[1].mark = Some(Status::Urgent);
+        let expect = indoc!{"
+            - [x] Call friends to
+                  ask who's coming
+
+              * [!] Call Alice
+        "};
+        assert_eq!(save_items_via_string(&items), expect);
+    }
+
+    #[test]
+    fn test_save_with_changed_memo() {
+        let str = indoc!{"
+            - [ ] Call friends to
+                  ask who's coming
+            - [ ]   Call Alice\t
+            - [ ] Call Bob
+        "};
+        let mut items = load_items_via_str(str).unwrap();
+        items[0].memo = Some("Call friends to\nask who's coming\non Friday".into());
+        items[1].memo = Some("Call Alice\nat home".into());
+        let expect = indoc!{"
+            - [ ] Call friends to
+                  ask who's coming
+                  on Friday
+            - [ ]   Call Alice\t
+                    at home
+            - [ ] Call Bob
+        "};
+        assert_eq!(save_items_via_string(&items), expect);
+    }
+
+    #[test]
+    fn test_save_with_changed_due() {
+        let str = indoc!{"
+            - [ ] Send invitations #due:2026-10-20
+            - [ ] Call Alice
+        "};
+        let mut items = load_items_via_str(str).unwrap();
+        assert_eq!(save_items_via_string(&items), str);
+        items[0].set_due(crate::item::due::Due::parse("2026-10-21T17:00"));
+        items[1].set_due(crate::item::due::Due::parse("2026-10-22"));
+        let expect = indoc!{"
+            - [ ] Send invitations #due:2026-10-21T17:00
+            - [ ] Call Alice
+                  #due:2026-10-22
+        "};
+        assert_eq!(save_items_via_string(&items), expect);
+    }
+
+    #[test]
+    fn test_save_with_normalize_dates() {
+        let str = indoc!{"
+            - [ ] Send invitations #due:fri
+            - [ ] Call Alice #defer:+1w
+                  #due:2026-10-30
+        "};
+        let items = load_items_via_str(str).unwrap();
+        let now = chrono::NaiveDate::from_ymd_opt(2026, 10, 14).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let options = SaveOptions { normalize_dates: Some(now), ..Default::default() };
+        let expect = indoc!{"
+            - [ ] Send invitations #due:2026-10-16
+            - [ ] Call Alice #defer:2026-10-21
+                  #due:2026-10-30
+        "};
+        assert_eq!(save_items_via_string_with_options(&items, &options), expect);
+        assert_eq!(save_items_via_string_with_options(&items, &SaveOptions::default()), str);
+        let mut write: Vec<u8> = Vec::new();
+        save_items_via_write_with_options(&items, &mut write, &options).unwrap();
+        assert_eq!(write, expect.as_bytes());
+    }
+
+    #[test]
+    fn test_save_with_changed_mark_and_full_width_brackets() {
+        let mut items = load_items_via_str("\t* ［ ］ foo\n").unwrap();
+        items[0].mark = Some(Status::Done);
+        assert_eq!(save_items_via_string(&items), "\t* ［x］ foo\n");
+    }
+
+    #[test]
+    fn test_save_with_edited_cst_token() {
+        let mut items = load_items_via_str("- [ ] foo\n- [ ] goo\n").unwrap();
+        items[1].cst.as_mut().unwrap().list_marker = "*".into();
+        assert_eq!(save_items_via_string(&items), "- [ ] foo\n* [ ] goo\n");
+    }
+
+    #[test]
+    fn test_save_with_changed_nest() {
+        let str = indoc!{"
+            - [ ] foo
+                  goo
+        "};
+        let mut items = load_items_via_str(str).unwrap();
+        items[0].nest = Some(1);
+        let expect = "    - [ ] foo\n          goo\n";
+        assert_eq!(save_items_via_string(&items), expect);
+        items[0].memo = Some("foo\ngoo".into());
+        items[0].labels = Some(vec![crate::item::label::Label::new(["hoo"])]);
+        let expect = "    - [ ] foo\n          goo\n          #hoo\n";
+        assert_eq!(save_items_via_string(&items), expect);
+    }
+
+    #[test]
+    fn test_save_with_new_items() {
+        let mut items = load_items_via_str("[ ] foo\n").unwrap();
+        items.push(Item {
+            nest: Some(0),
+            mark: Some(Status::Todo),
+            memo: Some("goo\nhoo".into()),
+            ..Default::default()
+        });
+        items.push(Item {
+            nest: Some(1),
+            mark: Some(Status::Done),
+            memo: Some("joo".into()),
+            ..Default::default()
+        });
+        let expect = indoc!{"
+            [ ] foo
+            [ ] goo
+                hoo
+                [x] joo
+        "};
+        assert_eq!(save_items_via_string(&items), expect);
+    }
+
+    #[test]
+    fn test_save_item_via_splice() {
+        let str = indoc!{"
+            # Party
+
+            - [ ] Call friends to
+                  ask who's coming
+            - [ ] Call Alice
+        "};
+        let mut items = load_items_via_str(str).unwrap();
+        items[0].mark = Some(Status::Done);
+        items[0].memo = Some("Call friends".into());
+        let expect = indoc!{"
+            # Party
+
+            - [x] Call friends
+            - [ ] Call Alice
+        "};
+        assert_eq!(save_item_via_splice(str, &items[0]), Some(expect.into()));
+        assert_eq!(save_item_via_splice(str, &items[1]), Some(str.into()));
+        assert_eq!(save_item_via_splice("", &items[0]), None);
+        let new_item = Item { memo: Some("foo".into()), ..Default::default() };
+        assert_eq!(save_item_via_splice(str, &new_item), None);
+    }
+
+    #[test]
+    fn test_save_items_via_write() {
+        let str = "[ ] foo\n\n[x] goo\n";
+        let items = load_items_via_str(str).unwrap();
+        let mut write: Vec<u8> = Vec::new();
+        save_items_via_write(&items, &mut write).unwrap();
+        assert_eq!(write, str.as_bytes());
+    }
+
+    #[test]
+    fn test_save_items_via_path_with_includes() {
+        let dir = TestDir::new("save-includes");
+        let path = dir.join("todo.txt");
+        ::std::fs::write(&path, "---\ntitle: Party\n---\n[ ] foo\n#include:work.txt\n[ ] hoo\n").unwrap();
+        ::std::fs::write(dir.join("work.txt"), "# Work\n[ ] goo\n").unwrap();
+        let (meta, mut items) = load_list_via_path(&path).unwrap();
+        items[1].mark = Some(Status::Done);
+        items.insert(2, Item { nest: Some(0), mark: Some(Status::Todo), memo: Some("joo".into()), section: items[1].section.clone(), ..Default::default() });
+        save_list_via_path(&meta, &items, &path).unwrap();
+        assert_eq!(::std::fs::read_to_string(&path).unwrap(), "---\ntitle: Party\n---\n[ ] foo\n#include:work.txt\n[ ] hoo\n");
+        assert_eq!(::std::fs::read_to_string(dir.join("work.txt")).unwrap(), "# Work\n[x] goo\n[ ] joo\n");
+        assert_eq!(load_items_via_path(&path).unwrap(), items);
+        items.remove(2);
+        save_items_via_path(&items, &path).unwrap();
+        assert_eq!(::std::fs::read_to_string(dir.join("work.txt")).unwrap(), "# Work\n[x] goo\n");
+    }
+
+    #[test]
+    fn test_save_items_via_path_with_prose() {
+        let dir = TestDir::new("save-prose");
+        let path = dir.join("todo.txt");
+        ::std::fs::write(&path, "Intro prose\n\n# Work\n[ ] a #priority:2\n[ ] b\n[ ] c #priority:1\n").unwrap();
+        let mut items = load_items_via_path(&path).unwrap();
+        items.remove(1);
+        save_items_via_path(&items, &path).unwrap();
+        assert_eq!(::std::fs::read_to_string(&path).unwrap(), "Intro prose\n\n# Work\n[ ] a #priority:2\n[ ] c #priority:1\n");
+        let items = crate::item::priority::sort_items_by_priority(items, None);
+        save_items_via_path(&items, &path).unwrap();
+        assert_eq!(::std::fs::read_to_string(&path).unwrap(), "Intro prose\n\n# Work\n[ ] c #priority:1\n[ ] a #priority:2\n");
+    }
+
+    #[test]
+    fn test_save_items_via_path_without_items() {
+        let dir = TestDir::new("save-without-items");
+        let path = dir.join("todo.txt");
+        ::std::fs::write(&path, "[ ] foo\n").unwrap();
+        save_items_via_path(&[], &path).unwrap();
+        assert_eq!(::std::fs::read_to_string(&path).unwrap(), "[ ] foo\n");
+        ::std::fs::write(&path, "# Home\n[ ] foo\n#include:work.txt\n").unwrap();
+        ::std::fs::write(dir.join("work.txt"), "# Work\n[ ] goo\n[ ] hoo\n").unwrap();
+        let (mut meta, mut items) = load_list_via_path(&path).unwrap();
+        items.retain(|x| x.file.as_deref() == Some(path.as_path()));
+        save_items_via_path(&items, &path).unwrap();
+        assert_eq!(::std::fs::read_to_string(dir.join("work.txt")).unwrap(), "# Work\n");
+        meta.set("title", Some("Party"));
+        save_list_via_path(&meta, &[], &path).unwrap();
+        assert_eq!(::std::fs::read_to_string(&path).unwrap(), "---\ntitle: Party\n---\n# Home\n#include:work.txt\n");
+    }
+
+    #[test]
+    fn test_save_items_via_path_as_other_file() {
+        let dir = TestDir::new("save-as");
+        let a = dir.join("a.txt");
+        let b = dir.join("b.txt");
+        let a_str = "---\ntitle: Party\n---\n# Work\n[ ] foo\n#include:shared.txt\n";
+        ::std::fs::write(&a, a_str).unwrap();
+        ::std::fs::write(dir.join("shared.txt"), "[ ] goo\n").unwrap();
+        ::std::fs::write(&b, "[ ] hoo\n").unwrap();
+        let mut items = load_items_via_path(&a).unwrap();
+        items[0].mark = Some(Status::Done);
+        save_items_via_path(&items, &b).unwrap();
+        assert_eq!(::std::fs::read_to_string(&a).unwrap(), a_str);
+        assert_eq!(::std::fs::read_to_string(&b).unwrap(), "---\ntitle: Party\n---\n# Work\n[x] foo\n#include:shared.txt\n");
+        assert_eq!(::std::fs::read_to_string(dir.join("shared.txt")).unwrap(), "[ ] goo\n");
+        let c = dir.join("c.txt");
+        save_items_via_path(&items, &c).unwrap();
+        assert_eq!(::std::fs::read_to_string(&c).unwrap(), ::std::fs::read_to_string(&b).unwrap());
+        assert_eq!(::std::fs::read_to_string(&a).unwrap(), a_str);
+    }
+
+    #[test]
+    fn test_save_items_via_path_with_options() {
+        let dir = TestDir::new("save-with-options");
+        let path = dir.join("todo.txt");
+        ::std::fs::write(&path, "# Work\n* [ ] foo #due:tomorrow\n#include:work.txt\n").unwrap();
+        ::std::fs::write(dir.join("work.txt"), "* [ ] goo #defer:tomorrow\n").unwrap();
+        let mut items = load_items_via_path(&path).unwrap();
+        items.push(Item { nest: Some(0), mark: Some(Status::Todo), memo: Some("hoo".into()), ..Default::default() });
+        let options = SaveOptions {
+            normalize_dates: Some(chrono::NaiveDate::from_ymd_opt(2026, 10, 14).unwrap().and_hms_opt(9, 0, 0).unwrap()),
+            render: RenderOptions { list_marker: "*".into(), ..Default::default() },
+        };
+        save_items_via_path_with_options(&items, &path, &options).unwrap();
+        assert_eq!(::std::fs::read_to_string(&path).unwrap(), "# Work\n* [ ] foo #due:2026-10-15\n#include:work.txt\n");
+        assert_eq!(::std::fs::read_to_string(dir.join("work.txt")).unwrap(), "* [ ] goo #defer:2026-10-15\n* [ ] hoo\n");
+        let document = load_document_via_str("[ ] joo #due:tomorrow\n").unwrap();
+        save_document_via_path_with_options(&document, &path, &options).unwrap();
+        assert_eq!(::std::fs::read_to_string(&path).unwrap(), "[ ] joo #due:2026-10-15\n");
+    }
+
+    #[test]
+    fn test_save_items_via_path_with_sorted_includes() {
+        let dir = TestDir::new("save-sorted-includes");
+        let path = dir.join("todo.txt");
+        ::std::fs::write(&path, "[ ] a #priority:3\n#include:x.txt\n[ ] b #priority:1\n").unwrap();
+        ::std::fs::write(dir.join("x.txt"), "[ ] x #priority:2\n").unwrap();
+        let items = load_items_via_path(&path).unwrap();
+        let items = crate::item::priority::sort_items_by_priority(items, None);
+        let memos: Vec<_> = items.iter().map(|x| x.memo.clone().unwrap()).collect();
+        assert_eq!(memos, vec!["b #priority:1", "x #priority:2", "a #priority:3"]);
+        save_items_via_path(&items, &path).unwrap();
+        assert_eq!(::std::fs::read_to_string(&path).unwrap(), "[ ] b #priority:1\n#include:x.txt\n[ ] a #priority:3\n");
+        assert_eq!(::std::fs::read_to_string(dir.join("x.txt")).unwrap(), "[ ] x #priority:2\n");
+    }
+
+}
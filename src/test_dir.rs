@@ -0,0 +1,42 @@
+//! Temporary directories for the files of tests.
+
+use std::path::{Path, PathBuf};
+
+/// An empty directory for the files of a test, which is removed
+/// with its files when this is dropped, even if the test fails.
+///
+/// ```
+/// let dir = TestDir::new("load-includes");
+/// ::std::fs::write(dir.join("todo.txt"), "[ ] foo\n").unwrap();
+/// ```
+///
+pub(crate) struct TestDir {
+    path: PathBuf,
+}
+
+impl TestDir {
+
+    /// Create an empty directory, with a name that is unique to the
+    /// test and to this process, so tests can run at the same time.
+    pub(crate) fn new(name: &str) -> TestDir {
+        let path = ::std::env::temp_dir().join(format!("todoby-{}-{}", name, ::std::process::id()));
+        let _ = ::std::fs::remove_dir_all(&path);
+        ::std::fs::create_dir_all(&path).unwrap();
+        TestDir { path }
+    }
+
+}
+
+impl std::ops::Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = ::std::fs::remove_dir_all(&self.path);
+    }
+}
@@ -5,7 +5,14 @@
 // extern crate cursive_table_view;
 // extern crate cursive_tree_view;
 
-use crate::item::item::Item;
+use chrono::{Local, Weekday};
+use todoby::item::depend::DependencyGraph;
+use todoby::item::due::{next_weekday, Due};
+use todoby::item::item::Item;
+use todoby::item::label::Label;
+use todoby::item::recur::toggle;
+use todoby::item::section::group_items_by_section;
+use todoby::item::status::Status;
 
 use cursive::{
     Cursive,
@@ -24,35 +31,40 @@ fn demo_items() -> Vec<Item> {
     vec![
         Item {
             nest: Some(0),
-            mark: Some(" ".into()),
-            memo: Some("foo".into()),
-            label1s: None,
-            label2s: None,
+            mark: Some(Status::Todo),
+            memo: Some("foo #due:tomorrow #every:week".into()),
+            labels: Some(vec![Label::new(["due", "tomorrow"]), Label::new(["every", "week"])]),
+            section: Some(vec!["Home".into()]),
+            ..Default::default()
         },
         Item {
             nest: Some(0),
-            mark: Some("!".into()),
-            memo: Some("goo".into()),
-            label1s: None,
-            label2s: None,
+            mark: Some(Status::Urgent),
+            memo: Some("goo #due:2026-10-20".into()),
+            labels: Some(vec![Label::new(["due", "2026-10-20"])]),
+            section: Some(vec!["Work".into(), "Website".into()]),
+            ..Default::default()
         },
         Item {
             nest: Some(0),
-            mark: Some("x".into()),
+            mark: Some(Status::Done),
             memo: Some("hoo".into()),
-            label1s: None,
-            label2s: None,
+            labels: None,
+            section: Some(vec!["Home".into()]),
+            ..Default::default()
         },
     ]
 }
 
+// The demo UI is not opened by main yet.
+#[allow(dead_code)]
 fn ui() {
     let mut siv = cursive::default();
 
+    let items = demo_items();
     let mut select_view = SelectView::<String>::new();
-    for item in demo_items() {
-        select_view.add_item_str(item.to_string());
-    }
+    select_view.add_all_str(item_strings(&items));
+    siv.set_user_data(items);
 
     let select = select_view
         .on_submit(on_submit)
@@ -61,6 +73,9 @@ fn ui() {
     let buttons = LinearLayout::vertical()
         .child(Button::new("Add new", add_name))
         .child(Button::new("Delete", delete_name))
+        .child(Button::new("Toggle", toggle_item))
+        .child(Button::new("Due by Friday", due_by_friday))
+        .child(Button::new("By section", by_section))
         .child(DummyView)
         .child(Button::new("Quit", Cursive::quit));
 
@@ -78,6 +93,14 @@ fn add_name(s: &mut Cursive) {
         s.call_on_name("select", |view: &mut SelectView<String>| {
             view.add_item_str(name)
         });
+        s.with_user_data(|items: &mut Vec<Item>| {
+            items.push(Item {
+                nest: Some(0),
+                mark: Some(Status::Todo),
+                memo: Some(name.into()),
+                ..Default::default()
+            })
+        });
         s.pop_layer();
     }
 
@@ -104,10 +127,95 @@ fn delete_name(s: &mut Cursive) {
         None => s.add_layer(Dialog::info("No name to remove")),
         Some(focus) => {
             select.remove_item(focus);
+            s.with_user_data(|items: &mut Vec<Item>| {
+                items.remove(focus);
+            });
         }
     }
 }
 
+/// Toggle the selected item between done and to do; completing
+/// a recurring item adds its next occurrence after it.
+fn toggle_item(s: &mut Cursive) {
+    let focus = match s.find_name::<SelectView<String>>("select").unwrap().selected_id() {
+        None => return s.add_layer(Dialog::info("No item to toggle")),
+        Some(focus) => focus,
+    };
+    let now = Local::now().naive_local();
+    let strings = s.with_user_data(|items: &mut Vec<Item>| {
+        toggle(items, focus, now);
+        item_strings(items)
+    }).unwrap_or_default();
+    s.call_on_name("select", |view: &mut SelectView<String>| {
+        view.clear();
+        view.add_all_str(strings);
+        view.set_selection(focus)
+    });
+}
+
+fn due_by_friday(s: &mut Cursive) {
+    let now = Local::now().naive_local();
+    let friday = next_weekday(now.date(), Weekday::Fri);
+    let mut items: Vec<Item> = s.user_data::<Vec<Item>>().cloned().unwrap_or_default();
+    for item in items.iter_mut() {
+        item.normalize_dates(now);
+    }
+    let blocked = DependencyGraph::new(&items).blocked(&items);
+    let mut items: Vec<(Item, bool)> = items.into_iter().zip(blocked)
+        .filter(|(item, _)| item.mark != Some(Status::Done) && item.is_due_by(friday))
+        .collect();
+    items.sort_by(|a, b| a.0.cmp_by_due(&b.0));
+    let text = if items.is_empty() {
+        String::from("Nothing is due")
+    } else {
+        items.iter().map(|(item, blocked)| item_string(item, *blocked, item.due())).collect::<Vec<_>>().join("\n")
+    };
+    s.add_layer(Dialog::text(text)
+        .title(format!("Due by {}", friday))
+        .button("Ok", |s| {
+            s.pop_layer();
+        }));
+}
+
+/// Show the items grouped by section, under their section paths,
+/// such as `Work / Website`.
+fn by_section(s: &mut Cursive) {
+    let items: Vec<Item> = s.user_data::<Vec<Item>>().cloned().unwrap_or_default();
+    let strings = item_strings(&items);
+    let text = group_items_by_section(&items).into_iter()
+        .map(|(section, indexes)| {
+            let title = section.map_or("(no section)".into(), |x| x.join(" / "));
+            let lines: Vec<&str> = indexes.iter().map(|x| strings[*x].as_str()).collect();
+            format!("{}\n  {}", title, lines.join("\n  "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    s.add_layer(Dialog::text(if text.is_empty() { String::from("No items") } else { text })
+        .title("By section")
+        .button("Ok", |s| {
+            s.pop_layer();
+        }));
+}
+
+/// Get the text to show for each item, where a blocked item,
+/// which has a prerequisite that is not done, is not actionable.
+fn item_strings(items: &[Item]) -> Vec<String> {
+    let blocked = DependencyGraph::new(items).blocked(items);
+    items.iter().zip(blocked).map(|(item, blocked)| item_string(item, blocked, None)).collect()
+}
+
+fn item_string(item: &Item, blocked: bool, due: Option<Due>) -> String {
+    let mut s = String::new();
+    if let Some(due) = due {
+        s.push_str(&format!("{} ", due));
+    }
+    s.push_str(&item.to_string());
+    if blocked {
+        s.push_str(" (blocked)");
+    }
+    s
+}
+
 fn on_submit(s: &mut Cursive, name: &str) {
     s.pop_layer();
     s.add_layer(Dialog::text(format!("Name: {}\nAwesome: yes", name))